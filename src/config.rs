@@ -1,7 +1,38 @@
+use crate::models::string::{CaseMode, PalindromePolicy};
+use crate::services::hashing::HashAlgorithm;
 use std::env;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Memory,
+    File,
+    Sqlite,
+}
+
+impl StorageBackend {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "memory" => Some(StorageBackend::Memory),
+            "file" => Some(StorageBackend::File),
+            "sqlite" => Some(StorageBackend::Sqlite),
+            _ => None,
+        }
+    }
+}
 
 pub struct Config {
     pub port: u16,
+    pub storage_backend: StorageBackend,
+    pub data_dir: PathBuf,
+    pub snapshot_interval: usize,
+    pub palindrome_policy: PalindromePolicy,
+    pub enabled_analyzers: Option<Vec<String>>,
+    /// Algorithm for new string ids. Strings saved under a different algorithm
+    /// stay reachable through their stored digests.
+    pub id_algorithm: HashAlgorithm,
+    /// Directory of extra natural language lexicons, one JSON file per locale.
+    pub lexicon_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -10,7 +41,70 @@ impl Config {
             .unwrap_or_else(|_| "8080".to_string())
             .parse()
             .expect("PORT must be a number");
-        
-        Config { port }
+
+        let storage_backend = env::var("STORAGE_BACKEND")
+            .map(|val| StorageBackend::parse(&val).expect("STORAGE_BACKEND must be one of: memory, file, sqlite"))
+            .unwrap_or(StorageBackend::Memory);
+
+        let data_dir = env::var("DATA_DIR")
+            .unwrap_or_else(|_| "./data".to_string())
+            .into();
+
+        let snapshot_interval = env::var("SNAPSHOT_INTERVAL")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse()
+            .expect("SNAPSHOT_INTERVAL must be a number");
+
+        let defaults = PalindromePolicy::default();
+        let palindrome_policy = PalindromePolicy {
+            ignore_whitespace: env_bool("PALINDROME_IGNORE_WHITESPACE", defaults.ignore_whitespace),
+            ignore_punctuation: env_bool("PALINDROME_IGNORE_PUNCTUATION", defaults.ignore_punctuation),
+            ignore_diacritics: env_bool("PALINDROME_IGNORE_DIACRITICS", defaults.ignore_diacritics),
+            case_mode: env::var("PALINDROME_CASE_MODE")
+                .map(|val| match val.to_lowercase().as_str() {
+                    "sensitive" => CaseMode::Sensitive,
+                    "lowercase" => CaseMode::Lowercase,
+                    "fold" => CaseMode::Fold,
+                    _ => panic!("PALINDROME_CASE_MODE must be one of: sensitive, lowercase, fold"),
+                })
+                .unwrap_or(defaults.case_mode),
+        };
+
+        let enabled_analyzers = env::var("ANALYZERS").ok().map(|val| {
+            val.split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        });
+
+        let id_algorithm = env::var("ID_HASH")
+            .map(|val| match HashAlgorithm::parse(&val) {
+                Ok(algorithm) if algorithm.is_cryptographic() => algorithm,
+                _ => panic!("ID_HASH must be one of: sha256, sha512, sha1, blake3"),
+            })
+            .unwrap_or_default();
+
+        let lexicon_dir = env::var("LEXICON_DIR").ok().map(PathBuf::from);
+
+        Config {
+            port,
+            storage_backend,
+            data_dir,
+            snapshot_interval,
+            palindrome_policy,
+            enabled_analyzers,
+            id_algorithm,
+            lexicon_dir,
+        }
     }
-}
\ No newline at end of file
+}
+
+fn env_bool(name: &str, default: bool) -> bool {
+    env::var(name)
+        .map(|val| match val.to_lowercase().as_str() {
+            "true" | "1" => true,
+            "false" | "0" => false,
+            _ => panic!("{} must be true or false", name),
+        })
+        .unwrap_or(default)
+}
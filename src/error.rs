@@ -0,0 +1,233 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// A request parameter that failed validation, reported in `invalid_params`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidParam {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Every error the service reports. Each variant has a stable `code`, used as
+/// the RFC 7807 `type`, and an HTTP status.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// Query or body parameters that could not be parsed or are inconsistent.
+    InvalidParams(Vec<InvalidParam>),
+    /// A `filter=` expression that does not parse; `column` is 1-based.
+    FilterSyntax { filter: String, message: String, column: usize },
+    /// A body that could not be decoded.
+    InvalidBody(String),
+    /// A malformed request the other variants do not describe, such as a
+    /// query string or path that fails to deserialize.
+    BadRequest(String),
+    UnsupportedMediaType(String),
+    PayloadTooLarge(String),
+    NotFound(String),
+    MethodNotAllowed(String),
+    AlreadyExists(String),
+    /// A natural language query whose clauses contradict each other.
+    ConflictingFilters(String),
+    /// A natural language query with no recognizable clause.
+    UnrecognizedQuery(String),
+    Storage(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn invalid_param(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        AppError::InvalidParams(vec![InvalidParam {
+            name: name.into(),
+            reason: reason.into(),
+        }])
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::InvalidParams(_) => "invalid-params",
+            AppError::FilterSyntax { .. } => "filter-syntax",
+            AppError::InvalidBody(_) => "invalid-body",
+            AppError::BadRequest(_) => "bad-request",
+            AppError::UnsupportedMediaType(_) => "unsupported-media-type",
+            AppError::PayloadTooLarge(_) => "payload-too-large",
+            AppError::NotFound(_) => "not-found",
+            AppError::MethodNotAllowed(_) => "method-not-allowed",
+            AppError::AlreadyExists(_) => "already-exists",
+            AppError::ConflictingFilters(_) => "conflicting-filters",
+            AppError::UnrecognizedQuery(_) => "unrecognized-query",
+            AppError::Storage(_) => "storage-error",
+            AppError::Internal(_) => "internal-error",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            AppError::InvalidParams(_) => "Invalid request parameters",
+            AppError::FilterSyntax { .. } => "Invalid filter expression",
+            AppError::InvalidBody(_) => "Invalid request body",
+            AppError::BadRequest(_) => "Bad request",
+            AppError::UnsupportedMediaType(_) => "Unsupported media type",
+            AppError::PayloadTooLarge(_) => "Payload too large",
+            AppError::NotFound(_) => "Not found",
+            AppError::MethodNotAllowed(_) => "Method not allowed",
+            AppError::AlreadyExists(_) => "Already exists",
+            AppError::ConflictingFilters(_) => "Conflicting filters",
+            AppError::UnrecognizedQuery(_) => "Unrecognized query",
+            AppError::Storage(_) => "Storage error",
+            AppError::Internal(_) => "Internal error",
+        }
+    }
+
+    /// The RFC 7807 `type` URI reference.
+    pub fn type_uri(&self) -> String {
+        format!("/problems/{}", self.code())
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::InvalidParams(params) => {
+                let reasons: Vec<&str> = params.iter().map(|p| p.reason.as_str()).collect();
+                f.write_str(&reasons.join("; "))
+            }
+            AppError::FilterSyntax { message, .. } => f.write_str(message),
+            AppError::InvalidBody(detail)
+            | AppError::BadRequest(detail)
+            | AppError::UnsupportedMediaType(detail)
+            | AppError::PayloadTooLarge(detail)
+            | AppError::NotFound(detail)
+            | AppError::MethodNotAllowed(detail)
+            | AppError::AlreadyExists(detail)
+            | AppError::ConflictingFilters(detail)
+            | AppError::UnrecognizedQuery(detail)
+            | AppError::Storage(detail)
+            | AppError::Internal(detail) => f.write_str(detail),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Body of an `application/problem+json` response.
+#[derive(Debug, Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid_params: Vec<InvalidParam>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Set by `JsonErrorHandler`; matches the `X-Request-Id` response header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl From<&AppError> for ProblemDetails {
+    fn from(err: &AppError) -> Self {
+        let (invalid_params, filter, column) = match err {
+            AppError::InvalidParams(params) => (params.clone(), None, None),
+            AppError::FilterSyntax { filter, column, .. } => (Vec::new(), Some(filter.clone()), Some(*column)),
+            _ => (Vec::new(), None, None),
+        };
+        // Storage messages come from SQLite or the file system and can name
+        // paths or tables; they are logged, not returned.
+        let detail = match err {
+            AppError::Storage(_) => "The request could not be completed by the storage backend".to_string(),
+            _ => err.to_string(),
+        };
+        ProblemDetails {
+            type_uri: err.type_uri(),
+            title: err.title(),
+            status: err.status_code().as_u16(),
+            detail,
+            invalid_params,
+            filter,
+            column,
+            request_id: None,
+        }
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::InvalidParams(_)
+            | AppError::FilterSyntax { .. }
+            | AppError::InvalidBody(_)
+            | AppError::BadRequest(_)
+            | AppError::UnrecognizedQuery(_) => StatusCode::BAD_REQUEST,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            AppError::AlreadyExists(_) => StatusCode::CONFLICT,
+            AppError::ConflictingFilters(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Storage(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .content_type(PROBLEM_JSON)
+            .json(ProblemDetails::from(self))
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        AppError::Storage(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::Storage(err.to_string())
+    }
+}
+
+/// Collects parameter errors so a request reports all of them at once.
+#[derive(Debug, Default)]
+pub struct ParamErrors(Vec<InvalidParam>);
+
+impl ParamErrors {
+    pub fn push(&mut self, name: &str, reason: impl Into<String>) {
+        self.0.push(InvalidParam {
+            name: name.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    /// Parses `value` if present, recording a failure under `name`.
+    pub fn parse<T>(
+        &mut self,
+        name: &str,
+        value: Option<&str>,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> Option<T> {
+        match value.map(parse).transpose() {
+            Ok(parsed) => parsed,
+            Err(reason) => {
+                self.push(name, reason);
+                None
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::InvalidParams(self.0))
+        }
+    }
+}
@@ -1,26 +1,40 @@
 use actix_web::{web, App, HttpServer, middleware};
 use actix_cors::Cors;
-use string_analyzer_service::routes::string_routes;
+use string_analyzer_service::routes::{analyzer_routes, string_routes};
 use string_analyzer_service::config::Config;
+use string_analyzer_service::middleware::JsonErrorHandler;
+use string_analyzer_service::services::analyzer::AnalysisSettings;
+use string_analyzer_service::services::nlp_parser::Lexicons;
+use string_analyzer_service::services::storage;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
-    
+
     let config = Config::from_env();
     let port = config.port;
-    
-    log::info!("Starting server on port {}", port);
-    
-    HttpServer::new(|| {
+    let store = storage::build_store(&config)?;
+    let analysis = AnalysisSettings::from_config(&config).map_err(std::io::Error::other)?;
+    let analysis = web::Data::new(analysis);
+    let lexicons = Lexicons::from_config(&config).map_err(std::io::Error::other)?;
+    let lexicons = web::Data::new(lexicons);
+
+    log::info!("Starting server on port {} with {:?} storage", port, config.storage_backend);
+
+    HttpServer::new(move || {
         let cors = Cors::permissive();
-        
+
         App::new()
+            .app_data(store.clone())
+            .app_data(analysis.clone())
+            .app_data(lexicons.clone())
+            .wrap(JsonErrorHandler)
             .wrap(cors)
             .wrap(middleware::Logger::default())
             .configure(string_routes::configure)
+            .configure(analyzer_routes::configure)
     })
     .bind(("0.0.0.0", port))?
     .run()
     .await
-}
\ No newline at end of file
+}
@@ -1,10 +1,27 @@
 use actix_web::{
+    body::EitherBody,
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error,
+    error::{InternalError, JsonPayloadError},
+    http::{header, Method, StatusCode},
+    Error, HttpResponse,
 };
+use crate::error::{AppError, ProblemDetails, PROBLEM_JSON};
 use futures_util::future::LocalBoxFuture;
 use std::future::{ready, Ready};
+use uuid::Uuid;
 
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request ID that is echoed back rather than replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Tags every response with an `X-Request-Id` and rewrites error responses,
+/// including actix's own plain-text ones for unknown routes, wrong methods and
+/// rejected payloads, as `application/problem+json` carrying that ID.
+///
+/// Error responses that already have a body of their own, such as a rejected
+/// batch report, keep it and only gain the header. Errors an inner service
+/// returns instead of a response are rendered the same way.
 pub struct JsonErrorHandler;
 
 impl<S, B> Transform<S, ServiceRequest> for JsonErrorHandler
@@ -13,7 +30,7 @@ where
     S::Future: 'static,
     B: 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type InitError = ();
     type Transform = JsonErrorMiddleware<S>;
@@ -34,18 +51,120 @@ where
     S::Future: 'static,
     B: 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = client_request_id(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
+        let (method, path) = (req.method().clone(), req.path().to_string());
         let fut = self.service.call(req);
-        
+
         Box::pin(async move {
-            let res = fut.await?;
-            Ok(res)
+            let mut res = match fut.await {
+                Ok(res) => res,
+                Err(error) => {
+                    // No request comes back with an error, so the rendered
+                    // response travels inside it.
+                    let cause = error.to_string();
+                    let original = HttpResponse::from_error(error);
+                    let response = problem_response(&method, &path, &original, &request_id);
+                    return Err(InternalError::from_response(cause, response).into());
+                }
+            };
+
+            let status = res.status();
+            let is_error = status.is_client_error() || status.is_server_error();
+            let has_body = res.headers().contains_key(header::CONTENT_TYPE);
+            if is_error && (res.response().error().is_some() || !has_body) {
+                let (req, original) = res.into_parts();
+                let response = problem_response(&method, &path, &original, &request_id);
+                return Ok(ServiceResponse::new(req, response).map_into_right_body());
+            }
+
+            if let Ok(value) = header::HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(header::HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            Ok(res.map_into_left_body())
         })
     }
-}
\ No newline at end of file
+}
+
+/// The caller's own `X-Request-Id`, if it is short and printable enough to echo.
+fn client_request_id(req: &ServiceRequest) -> Option<String> {
+    let id = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?;
+    let valid = !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| id.to_string())
+}
+
+/// Names the failure behind an error response. Errors raised as `AppError`
+/// are kept as they are; framework errors are classified by status.
+fn classify(method: &Method, path: &str, status: StatusCode, error: Option<&Error>) -> Option<AppError> {
+    if let Some(app_error) = error.and_then(|e| e.as_error::<AppError>()) {
+        return Some(app_error.clone());
+    }
+    let detail = error.map(ToString::to_string);
+    let app_error = match status {
+        StatusCode::NOT_FOUND => {
+            AppError::NotFound(detail.unwrap_or_else(|| format!("No route for {} {}", method, path)))
+        }
+        StatusCode::METHOD_NOT_ALLOWED => AppError::MethodNotAllowed(
+            detail.unwrap_or_else(|| format!("{} is not allowed on {}", method, path)),
+        ),
+        StatusCode::PAYLOAD_TOO_LARGE => {
+            AppError::PayloadTooLarge(detail.unwrap_or_else(|| "Request body is too large".to_string()))
+        }
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => {
+            AppError::UnsupportedMediaType(detail.unwrap_or_else(|| "Unsupported media type".to_string()))
+        }
+        StatusCode::BAD_REQUEST if error.is_some_and(|e| e.as_error::<JsonPayloadError>().is_some()) => {
+            AppError::InvalidBody(detail.unwrap_or_default())
+        }
+        StatusCode::BAD_REQUEST => AppError::BadRequest(detail.unwrap_or_else(|| "Bad request".to_string())),
+        status if status.is_server_error() => {
+            AppError::Internal(detail.unwrap_or_else(|| "Internal server error".to_string()))
+        }
+        _ => return None,
+    };
+    Some(app_error)
+}
+
+fn problem_response<B>(method: &Method, path: &str, original: &HttpResponse<B>, request_id: &str) -> HttpResponse {
+    let status = original.status();
+    let error = original.error();
+    let mut problem = match classify(method, path, status, error) {
+        Some(app_error) => ProblemDetails::from(&app_error),
+        // RFC 7807: `about:blank` means the status code says it all.
+        None => ProblemDetails {
+            type_uri: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail: error.map(ToString::to_string).unwrap_or_default(),
+            invalid_params: Vec::new(),
+            filter: None,
+            column: None,
+            request_id: None,
+        },
+    };
+    // The original status wins, so a framework error keeps its code.
+    problem.status = status.as_u16();
+    problem.request_id = Some(request_id.to_string());
+    if status.is_server_error() {
+        // The full error, which the problem detail may leave out.
+        let cause = error.map_or_else(|| problem.detail.clone(), ToString::to_string);
+        log::error!("Request {} failed: {}", request_id, cause);
+    }
+
+    let mut builder = HttpResponse::build(status);
+    for (name, value) in original.headers() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            builder.append_header((name.clone(), value.clone()));
+        }
+    }
+    builder
+        .insert_header((REQUEST_ID_HEADER, request_id))
+        .content_type(PROBLEM_JSON)
+        .json(problem)
+}
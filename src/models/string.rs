@@ -1,20 +1,83 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use crate::error::AppError;
+use crate::services::hashing::{parse_algorithms, HashAlgorithm};
+use crate::utils::filters::Pattern;
+use crate::utils::filters::expr::FilterExpr;
 
 #[derive(Debug, Deserialize)]
 pub struct StringInput {
     pub value: String,
+    #[serde(default)]
+    pub palindrome_policy: PalindromePolicyOverride,
+}
+
+/// Body of `POST /analyze`: one input or an array of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum AnalyzeInput {
+    One(StringInput),
+    Many(Vec<StringInput>),
+}
+
+/// Query options shared by every endpoint that runs the analyzer.
+#[derive(Debug, Deserialize, Default)]
+pub struct AnalysisQuery {
+    /// Comma-separated extra digests, e.g. `blake3,sha512`.
+    pub hashes: Option<String>,
+}
+
+impl AnalysisQuery {
+    pub fn hash_algorithms(&self) -> Result<Vec<HashAlgorithm>, AppError> {
+        self.hashes
+            .as_deref()
+            .map(parse_algorithms)
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(|e| AppError::invalid_param("hashes", e))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalysisResult {
+    pub value: String,
+    pub properties: StringProperties,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StringProperties {
+    /// Length in extended grapheme clusters; same as `grapheme_count`.
     pub length: usize,
+    #[serde(default)]
+    pub byte_length: usize,
+    #[serde(default)]
+    pub code_point_count: usize,
+    #[serde(default)]
+    pub grapheme_count: usize,
     pub is_palindrome: bool,
+    /// Normalization that was applied before `is_palindrome` was decided.
+    #[serde(default)]
+    pub palindrome_policy: PalindromePolicy,
     pub unique_characters: usize,
     pub word_count: usize,
+    #[serde(default)]
+    pub vowel_count: usize,
+    #[serde(default)]
+    pub consonant_count: usize,
+    #[serde(default)]
+    pub digit_count: usize,
+    #[serde(default)]
+    pub punctuation_count: usize,
     pub sha256_hash: String,
-    pub character_frequency_map: HashMap<char, usize>,
+    pub character_frequency_map: HashMap<String, usize>,
+    /// Extra digests of the value, keyed by algorithm.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hashes: BTreeMap<HashAlgorithm, String>,
+    /// Results of the registered custom analyzers, keyed by analyzer name and
+    /// serialized alongside the built-in properties.
+    #[serde(flatten, default)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -25,17 +88,129 @@ pub struct StoredString {
     pub created_at: DateTime<Utc>,
 }
 
+impl StoredString {
+    /// Every digest of the value with the algorithm that produced it: the
+    /// SHA-256 digest first, then `hashes`.
+    pub fn digests(&self) -> impl Iterator<Item = (HashAlgorithm, &str)> {
+        std::iter::once((HashAlgorithm::Sha256, self.properties.sha256_hash.as_str()))
+            .chain(self.properties.hashes.iter().map(|(algorithm, digest)| (*algorithm, digest.as_str())))
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct FilteredResponse {
     pub data: Vec<StoredString>,
     pub count: usize,
     pub filters_applied: Filters,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct InterpretedQuery {
     pub original: String,
+    /// Locale of the lexicon the query was read with.
+    pub language: String,
     pub parsed_filters: Filters,
+    /// How sure the parser is of this reading, from 0 to 1.
+    pub confidence: f64,
+    pub clauses: Vec<QueryClause>,
+    /// Words no rule used. Filler such as "the" or "strings" is not listed.
+    pub ignored_words: Vec<QueryWord>,
+    /// Ordering asked for by the query, as in "the 5 longest palindromes".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<SortKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Other readings of an ambiguous query, most likely first. Results
+    /// always follow `parsed_filters`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<Interpretation>,
+}
+
+/// One reading of a natural language query.
+#[derive(Debug, Serialize)]
+pub struct Interpretation {
+    pub parsed_filters: Filters,
+    pub confidence: f64,
+    pub clauses: Vec<QueryClause>,
+}
+
+/// The part of a query that produced one filter. `start` and `end` are
+/// character offsets into the query, end exclusive.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct QueryClause {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    /// The filter in `filter=` expression syntax. Sorting and limiting
+    /// clauses give the equivalent listing parameters instead.
+    pub filter: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct QueryWord {
+    pub word: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Created,
+    Conflict,
+    Invalid,
+    /// Valid, but not inserted because an atomic batch was rejected.
+    Skipped,
+    /// Would be created; reported by dry runs.
+    Valid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub status: BatchItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub results: Vec<BatchItemResult>,
+    pub created: usize,
+    pub conflicts: usize,
+    pub invalid: usize,
+    pub atomic: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub results: Vec<BatchItemResult>,
+    /// Items created, or that would be created when `dry_run` is set.
+    pub imported: usize,
+    pub conflicts: usize,
+    pub invalid: usize,
+    pub dry_run: bool,
+}
+
+/// Trailing line of an NDJSON listing, written as `{"summary": {...}}` after
+/// the last match.
+#[derive(Debug, Serialize)]
+pub struct StreamSummary {
+    pub count: usize,
+    pub filters_applied: Filters,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// The natural language query, for `filter-by-natural-language`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -51,5 +226,152 @@ pub struct Filters {
     pub min_length: Option<usize>,
     pub max_length: Option<usize>,
     pub word_count: Option<usize>,
-    pub contains_character: Option<char>,
-}
\ No newline at end of file
+    /// One grapheme cluster, which may span several code points.
+    pub contains_character: Option<String>,
+    /// Grapheme clusters, each of which may span several code points.
+    pub contains_characters: Option<Vec<String>>,
+    pub contains_mode: Option<ContainsMode>,
+    pub case_insensitive: Option<bool>,
+    pub contains: Option<String>,
+    pub starts_with: Option<String>,
+    pub ends_with: Option<String>,
+    pub matches: Option<Pattern>,
+    pub min_word_count: Option<usize>,
+    pub max_word_count: Option<usize>,
+    pub min_unique_characters: Option<usize>,
+    pub max_unique_characters: Option<usize>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub filter: Option<FilterExpr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    #[default]
+    CreatedAt,
+    Length,
+    WordCount,
+    UniqueCharacters,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 4] = [SortKey::CreatedAt, SortKey::Length, SortKey::WordCount, SortKey::UniqueCharacters];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::CreatedAt => "created_at",
+            SortKey::Length => "length",
+            SortKey::WordCount => "word_count",
+            SortKey::UniqueCharacters => "unique_characters",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn name(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainsMode {
+    #[default]
+    All,
+    Any,
+}
+
+/// Kinds of character the analyzer counts. Vowels and consonants are Latin
+/// letters compared without diacritics; "y" is a consonant.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CharClass {
+    Vowel,
+    Consonant,
+    Digit,
+    Punctuation,
+}
+
+impl CharClass {
+    pub const ALL: [CharClass; 4] = [CharClass::Vowel, CharClass::Consonant, CharClass::Digit, CharClass::Punctuation];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "vowel" => Some(CharClass::Vowel),
+            "consonant" => Some(CharClass::Consonant),
+            "digit" => Some(CharClass::Digit),
+            "punctuation" => Some(CharClass::Punctuation),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CharClass::Vowel => "vowel",
+            CharClass::Consonant => "consonant",
+            CharClass::Digit => "digit",
+            CharClass::Punctuation => "punctuation",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseMode {
+    Sensitive,
+    #[default]
+    Lowercase,
+    Fold,
+}
+
+/// How a value is normalized before checking whether it is a palindrome.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PalindromePolicy {
+    pub ignore_whitespace: bool,
+    pub ignore_punctuation: bool,
+    pub ignore_diacritics: bool,
+    pub case_mode: CaseMode,
+}
+
+impl Default for PalindromePolicy {
+    fn default() -> Self {
+        PalindromePolicy {
+            ignore_whitespace: true,
+            ignore_punctuation: false,
+            ignore_diacritics: false,
+            case_mode: CaseMode::Lowercase,
+        }
+    }
+}
+
+/// Per-request palindrome settings; unset fields fall back to the server policy.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct PalindromePolicyOverride {
+    pub ignore_whitespace: Option<bool>,
+    pub ignore_punctuation: Option<bool>,
+    pub ignore_diacritics: Option<bool>,
+    pub case_mode: Option<CaseMode>,
+}
+
+impl PalindromePolicyOverride {
+    pub fn apply(&self, base: PalindromePolicy) -> PalindromePolicy {
+        PalindromePolicy {
+            ignore_whitespace: self.ignore_whitespace.unwrap_or(base.ignore_whitespace),
+            ignore_punctuation: self.ignore_punctuation.unwrap_or(base.ignore_punctuation),
+            ignore_diacritics: self.ignore_diacritics.unwrap_or(base.ignore_diacritics),
+            case_mode: self.case_mode.unwrap_or(base.case_mode),
+        }
+    }
+}
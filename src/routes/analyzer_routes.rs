@@ -0,0 +1,58 @@
+use actix_web::{web, HttpRequest, HttpResponse, http::StatusCode};
+use crate::error::AppError;
+use crate::models::string::{AnalysisQuery, AnalysisResult, AnalyzeInput};
+use crate::services::analyzer::AnalysisSettings;
+use crate::utils::format::Body;
+use crate::utils::projection::{parse_projection, ProjectionQuery};
+use crate::utils::response::{projected_response, success_response};
+use serde_json::json;
+
+/// Upper bound on the number of values one `POST /analyze` call may carry.
+const MAX_ANALYZE_ITEMS: usize = 1000;
+
+pub async fn list_analyzers(settings: web::Data<AnalysisSettings>, req: HttpRequest) -> HttpResponse {
+    let analyzers = settings.registry.list();
+    let count = analyzers.len();
+    success_response(&req, json!({ "analyzers": analyzers, "count": count }), StatusCode::OK)
+}
+
+/// Runs the analyzer pipeline without touching the store.
+pub async fn analyze(
+    settings: web::Data<AnalysisSettings>,
+    req: HttpRequest,
+    query: web::Query<AnalysisQuery>,
+    fields: web::Query<ProjectionQuery>,
+    body: Body<AnalyzeInput>,
+) -> Result<HttpResponse, AppError> {
+    let algorithms = query.hash_algorithms()?;
+    let projection = parse_projection(&fields)?;
+
+    match body.into_inner() {
+        AnalyzeInput::One(input) => {
+            let properties = settings.analyze_input_projected(&input, &algorithms, &projection)?;
+            Ok(projected_response(&req, AnalysisResult { value: input.value, properties }, &projection, StatusCode::OK))
+        }
+        AnalyzeInput::Many(inputs) => {
+            if inputs.len() > MAX_ANALYZE_ITEMS {
+                return Err(AppError::InvalidBody(format!(
+                    "At most {} values can be analyzed per request",
+                    MAX_ANALYZE_ITEMS
+                )));
+            }
+            let mut data = Vec::with_capacity(inputs.len());
+            for (index, input) in inputs.into_iter().enumerate() {
+                let properties = settings
+                    .analyze_input_projected(&input, &algorithms, &projection)
+                    .map_err(|e| AppError::invalid_param(format!("[{}].value", index), format!("Item {}: {}", index, e)))?;
+                data.push(AnalysisResult { value: input.value, properties });
+            }
+            let count = data.len();
+            Ok(projected_response(&req, json!({ "data": data, "count": count }), &projection, StatusCode::OK))
+        }
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/analyzers", web::get().to(list_analyzers))
+        .route("/analyze", web::post().to(analyze));
+}
@@ -1 +1,2 @@
+pub mod analyzer_routes;
 pub mod string_routes;
\ No newline at end of file
@@ -1,112 +1,536 @@
-use actix_web::{web, HttpResponse, http::StatusCode};
-use crate::models::string::{StringInput, StoredString, FilteredResponse, NaturalLanguageResponse, InterpretedQuery};
-use crate::services::analyzer::analyze_string;
-use crate::services::storage;
-use crate::services::nlp_parser::parse_natural_language;
-use crate::utils::filters::{parse_filters, QueryParams};
-use crate::utils::response::{success_response, error_response};
+use actix_web::{guard, web, HttpRequest, HttpResponse, http::{header, StatusCode}};
+use crate::error::{AppError, ParamErrors};
+use crate::models::string::{AnalysisQuery, BatchItemResult, BatchItemStatus, BatchResponse, Filters, ImportResponse, StreamSummary, StringInput, StoredString, FilteredResponse, NaturalLanguageResponse};
+use crate::services::analyzer::AnalysisSettings;
+use crate::services::hashing::compute_sha256;
+use crate::services::storage::{PagedRows, SharedStore};
+use crate::services::transfer::{columnar_body, csv_header, csv_line, ndjson_line, parse_import, ImportMode, TransferFormat};
+use crate::services::nlp_parser::{interpret_natural_language_in, Lexicons};
+use crate::utils::filters::{parse_bool, parse_filters, parse_filter_expression, QueryParams};
+use crate::utils::pagination::{parse_pagination, Cursor, Pagination};
+use crate::utils::format::{Body, Format};
+use crate::utils::projection::{parse_projection, ProjectionQuery};
+use crate::utils::response::{accepts_ndjson, NDJSON, ndjson_response, projected_response, success_response};
 use chrono::Utc;
+use futures_util::{stream, StreamExt};
+use rayon::prelude::*;
 use serde::Deserialize;
+use std::collections::HashSet;
 
-pub async fn create_string(body: Result<web::Json<StringInput>, actix_web::Error>) -> HttpResponse {
-    let body = match body {
-        Ok(b) => b,
-        Err(_) => return error_response("Invalid request body or missing value field", StatusCode::BAD_REQUEST),
-    };
+/// Upper bounds for `POST /strings/batch`.
+const MAX_BATCH_ITEMS: usize = 10_000;
+const MAX_BATCH_BYTES: usize = 16 * 1024 * 1024;
+const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+pub async fn create_string(
+    store: SharedStore,
+    settings: web::Data<AnalysisSettings>,
+    req: HttpRequest,
+    query: web::Query<AnalysisQuery>,
+    fields: web::Query<ProjectionQuery>,
+    body: Body<StringInput>,
+) -> Result<HttpResponse, AppError> {
+    let algorithms = query.hash_algorithms()?;
+    let projection = parse_projection(&fields)?;
     
     let value = &body.value;
+    let properties = settings.analyze_input(&body, &algorithms)?;
     
-    if value.is_empty() {
-        return error_response("value field cannot be empty", StatusCode::BAD_REQUEST);
-    }
-    
-    let properties = analyze_string(value);
-    let hash = properties.sha256_hash.clone();
-    
-    if storage::string_exists(&hash) {
-        return error_response("String already exists", StatusCode::CONFLICT);
+    // Checked by value rather than id: strings saved under a previous id
+    // algorithm must still be detected as duplicates.
+    if store.get_string_by_value(value)?.is_some() {
+        return Err(AppError::AlreadyExists("String already exists".to_string()));
     }
     
     let stored = StoredString {
-        id: hash,
+        id: settings.id_for(value),
         value: value.clone(),
         properties,
         created_at: Utc::now(),
     };
     
-    match storage::save_string(stored.clone()) {
-        Ok(_) => success_response(stored, StatusCode::CREATED),
-        Err(e) => error_response(&e, StatusCode::INTERNAL_SERVER_ERROR),
+    store.save_string(stored.clone())?;
+    Ok(projected_response(&req, stored, &projection, StatusCode::CREATED))
+}
+
+#[derive(Deserialize)]
+pub struct BatchQuery {
+    atomic: Option<String>,
+    #[serde(flatten)]
+    analysis: AnalysisQuery,
+}
+
+/// Splits a batch body into items. NDJSON is used when the request is sent as
+/// `application/x-ndjson`, or is JSON whose body does not start with `[`; the
+/// other negotiable formats carry an array. Items that fail to parse are kept
+/// as errors so they can be reported per index.
+fn parse_batch_body(req: &HttpRequest, body: &[u8]) -> Result<Vec<Result<StringInput, String>>, AppError> {
+    let declared_ndjson = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with(NDJSON));
+    let format = if declared_ndjson { Format::Json } else { Format::from_content_type(req).map_err(AppError::UnsupportedMediaType)? };
+
+    if format != Format::Json {
+        let items = match format.decode::<serde_json::Value>(body).map_err(AppError::InvalidBody)? {
+            serde_json::Value::Array(items) => items,
+            other => vec![other],
+        };
+        return Ok(items
+            .into_iter()
+            .map(|item| serde_json::from_value::<StringInput>(item).map_err(|e| format!("Invalid item: {}", e)))
+            .collect());
     }
+
+    let is_ndjson = declared_ndjson || body.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'[');
+    if is_ndjson {
+        let text = std::str::from_utf8(body).map_err(|_| AppError::InvalidBody("Request body is not valid UTF-8".to_string()))?;
+        Ok(text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str::<StringInput>(line).map_err(|e| format!("Invalid item: {}", e)))
+            .collect())
+    } else {
+        let items: Vec<serde_json::Value> =
+            serde_json::from_slice(body).map_err(|e| AppError::InvalidBody(format!("Invalid JSON array: {}", e)))?;
+        Ok(items
+            .into_iter()
+            .map(|item| serde_json::from_value::<StringInput>(item).map_err(|e| format!("Invalid item: {}", e)))
+            .collect())
+    }
+}
+
+fn batch_result(index: usize, value: Option<String>, status: BatchItemStatus, reason: Option<String>) -> BatchItemResult {
+    BatchItemResult { index, value, status, id: None, reason }
+}
+
+fn count_status(results: &[BatchItemResult], status: BatchItemStatus) -> usize {
+    results.iter().filter(|r| r.status == status).count()
 }
 
-pub async fn get_string(path: web::Path<String>) -> HttpResponse {
+/// Runs `analyze` over `items` on the blocking pool, spread across rayon's
+/// threads, since analysis is CPU-bound.
+async fn analyze_in_parallel<T, F>(
+    items: Vec<T>,
+    analyze: F,
+) -> Result<Vec<Result<StoredString, String>>, actix_web::error::BlockingError>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Result<StoredString, String> + Send + Sync + 'static,
+{
+    web::block(move || items.into_par_iter().map(analyze).collect()).await
+}
+
+/// Items ready to save, with their index in the request.
+type Pending = Vec<(usize, StoredString)>;
+
+/// Sorts analyzed items into invalid ones, conflicts with the store or with an
+/// earlier item, and those ready to save. Every item gets a result in input
+/// order; the ready ones are reported as created and returned with their index.
+fn classify_items(
+    store: &SharedStore,
+    analyzed: Vec<Result<StoredString, String>>,
+) -> Result<(Vec<BatchItemResult>, Pending), AppError> {
+    let mut results = Vec::with_capacity(analyzed.len());
+    let mut pending = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, item) in analyzed.into_iter().enumerate() {
+        match item {
+            Err(reason) => results.push(batch_result(index, None, BatchItemStatus::Invalid, Some(reason))),
+            Ok(stored) if !seen.insert(stored.properties.sha256_hash.clone()) => results.push(batch_result(
+                index,
+                Some(stored.value),
+                BatchItemStatus::Conflict,
+                Some("Duplicate of an earlier item in the batch".to_string()),
+            )),
+            Ok(stored) if store.get_string_by_value(&stored.value)?.is_some() => results.push(batch_result(
+                index,
+                Some(stored.value),
+                BatchItemStatus::Conflict,
+                Some("String already exists".to_string()),
+            )),
+            Ok(stored) => {
+                results.push(BatchItemResult {
+                    index,
+                    value: Some(stored.value.clone()),
+                    status: BatchItemStatus::Created,
+                    id: Some(stored.id.clone()),
+                    reason: None,
+                });
+                pending.push((index, stored));
+            }
+        }
+    }
+    Ok((results, pending))
+}
+
+/// Saves `pending` one by one, turning items that lost a race with another
+/// writer into conflicts.
+fn save_pending(store: &SharedStore, results: &mut [BatchItemResult], pending: Pending) {
+    for (index, stored) in pending {
+        if let Err(e) = store.save_string(stored) {
+            let result = &mut results[index];
+            result.status = BatchItemStatus::Conflict;
+            result.id = None;
+            result.reason = Some(e.to_string());
+        }
+    }
+}
+
+pub async fn create_strings_batch(
+    store: SharedStore,
+    settings: web::Data<AnalysisSettings>,
+    query: web::Query<BatchQuery>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    let atomic = query
+        .atomic
+        .as_deref()
+        .map(parse_bool)
+        .transpose()
+        .map_err(|e| AppError::invalid_param("atomic", e))?
+        .unwrap_or(false);
+    let algorithms = query.analysis.hash_algorithms()?;
+    let items = parse_batch_body(&req, &body)?;
+    if items.is_empty() {
+        return Err(AppError::InvalidBody("Batch must contain at least one item".to_string()));
+    }
+    if items.len() > MAX_BATCH_ITEMS {
+        return Err(AppError::InvalidBody(format!(
+            "At most {} items can be created per batch",
+            MAX_BATCH_ITEMS
+        )));
+    }
+
+    let analysis = settings.clone();
+    let analyzed = analyze_in_parallel(items, move |item| {
+        let input = item?;
+        let properties = analysis.analyze_input(&input, &algorithms).map_err(|e| e.to_string())?;
+        Ok(StoredString {
+            id: analysis.id_for(&input.value),
+            value: input.value,
+            properties,
+            created_at: Utc::now(),
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    let (mut results, pending) = classify_items(&store, analyzed)?;
+
+    let conflicts = count_status(&results, BatchItemStatus::Conflict);
+    let invalid = count_status(&results, BatchItemStatus::Invalid);
+
+    let status = if atomic {
+        let rejection = if conflicts + invalid > 0 {
+            Some(format!("Batch rejected: {} conflicting and {} invalid items", conflicts, invalid))
+        } else {
+            store.save_all(pending.into_iter().map(|(_, s)| s).collect()).err().map(|e| e.to_string())
+        };
+        match rejection {
+            None => StatusCode::CREATED,
+            Some(reason) => {
+                for result in results.iter_mut().filter(|r| r.status == BatchItemStatus::Created) {
+                    result.status = BatchItemStatus::Skipped;
+                    result.id = None;
+                    result.reason = Some(reason.clone());
+                }
+                if invalid > 0 { StatusCode::BAD_REQUEST } else { StatusCode::CONFLICT }
+            }
+        }
+    } else {
+        save_pending(&store, &mut results, pending);
+        StatusCode::OK
+    };
+
+    let response = BatchResponse {
+        created: count_status(&results, BatchItemStatus::Created),
+        conflicts: count_status(&results, BatchItemStatus::Conflict),
+        invalid,
+        atomic,
+        results,
+    };
+    Ok(success_response(&req, response, status))
+}
+
+pub async fn get_string(
+    store: SharedStore,
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<AnalysisQuery>,
+    fields: web::Query<ProjectionQuery>,
+) -> Result<HttpResponse, AppError> {
+    let algorithms = query.hash_algorithms()?;
+    let projection = parse_projection(&fields)?;
     let value = path.into_inner();
     
-    match storage::get_string_by_value(&value) {
-        Some(stored) => success_response(stored, StatusCode::OK),
-        None => error_response("String not found", StatusCode::NOT_FOUND),
+    let mut stored = store.get_string_by_value(&value)?.ok_or_else(string_not_found)?;
+    // Digests that were not requested at creation are computed on the fly,
+    // unless the projection drops them anyway.
+    let algorithms = if projection.wants_property("hashes") { algorithms } else { Vec::new() };
+    for algorithm in algorithms {
+        stored
+            .properties
+            .hashes
+            .entry(algorithm)
+            .or_insert_with(|| algorithm.digest(&stored.value));
     }
+    Ok(projected_response(&req, stored, &projection, StatusCode::OK))
 }
 
-pub async fn get_all_strings(query: web::Query<QueryParams>) -> HttpResponse {
-    let filters = match parse_filters(query) {
-        Ok(f) => f,
-        Err(e) => return error_response(&e, StatusCode::BAD_REQUEST),
-    };
+fn string_not_found() -> AppError {
+    AppError::NotFound("String not found".to_string())
+}
+
+pub async fn get_string_by_digest(
+    store: SharedStore,
+    req: HttpRequest,
+    path: web::Path<String>,
+    fields: web::Query<ProjectionQuery>,
+) -> Result<HttpResponse, AppError> {
+    let projection = parse_projection(&fields)?;
+    let stored = store.get_string_by_digest(&path.into_inner())?.ok_or_else(string_not_found)?;
+    Ok(projected_response(&req, stored, &projection, StatusCode::OK))
+}
+
+/// Builds the full `Filters`, including a `filter=` expression, from the
+/// listing query parameters.
+fn filters_from_query(query: web::Query<QueryParams>) -> Result<Filters, AppError> {
+    let expression = parse_filter_expression(&query)?;
+    let mut filters = parse_filters(query)?;
+    filters.filter = expression;
+    Ok(filters)
+}
+
+pub async fn get_all_strings(
+    store: SharedStore,
+    req: HttpRequest,
+    query: web::Query<QueryParams>,
+    fields: web::Query<ProjectionQuery>,
+) -> Result<HttpResponse, AppError> {
+    let projection = parse_projection(&fields)?;
+    let pagination = parse_pagination(&query)?;
+    let filters = filters_from_query(query)?;
     
-    let data = storage::get_all_strings(filters.clone());
+    if accepts_ndjson(&req) {
+        let rows = PagedRows::new(store.clone(), filters.clone(), pagination);
+        return Ok(ndjson_response(rows, &projection, move |rows, count| StreamSummary {
+            count,
+            filters_applied: filters,
+            next_cursor: rows.next_cursor().map(Cursor::encode),
+            original: None,
+        }));
+    }
+
+    let (data, next_cursor) = store.get_page(&filters, &pagination)?;
     let count = data.len();
-    
     let response = FilteredResponse {
         data,
         count,
         filters_applied: filters,
+        next_cursor: next_cursor.as_ref().map(Cursor::encode),
     };
     
-    success_response(response, StatusCode::OK)
+    Ok(projected_response(&req, response, &projection, StatusCode::OK))
 }
 
 #[derive(Deserialize)]
-pub struct NLQuery {
-    query: String,
+pub struct ExportQuery {
+    format: Option<String>,
 }
 
-pub async fn filter_by_natural_language(query: web::Query<NLQuery>) -> HttpResponse {
-    let query_str = &query.query;
-    
-    let (filters, original) = match parse_natural_language(query_str) {
-        Ok(result) => result,
-        Err(e) => {
-            if e.contains("Conflicting") {
-                return error_response(&e, StatusCode::UNPROCESSABLE_ENTITY);
+/// Streams every stored string matching the listing filters, oldest first.
+pub async fn export_strings(
+    store: SharedStore,
+    export: web::Query<ExportQuery>,
+    query: web::Query<QueryParams>,
+) -> Result<HttpResponse, AppError> {
+    let format = export
+        .format
+        .as_deref()
+        .map(TransferFormat::parse)
+        .transpose()
+        .map_err(|e| AppError::invalid_param("format", e))?
+        .unwrap_or_default();
+    let filters = filters_from_query(query)?;
+
+    let mut response = HttpResponse::Ok();
+    response.content_type(format.content_type()).insert_header((
+        actix_web::http::header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"strings.{}\"", format.extension()),
+    ));
+    let line = match format {
+        TransferFormat::Ndjson => ndjson_line,
+        TransferFormat::Csv => csv_line,
+        TransferFormat::Columnar => {
+            let (data, _) = store.get_page(&filters, &Pagination::default())?;
+            let body = columnar_body(&data).map_err(AppError::Internal)?;
+            return Ok(response.body(body));
+        }
+    };
+
+    // Rows are read from the store and encoded as the body is polled rather
+    // than buffered up front.
+    let header = (format == TransferFormat::Csv).then(csv_header).map(|header| header.map_err(AppError::Internal));
+    let rows = PagedRows::new(store.clone(), filters, Pagination::default())
+        .map(move |row| row.and_then(|string| line(&string).map_err(AppError::Internal)));
+    let body = stream::iter(header.into_iter().chain(rows)).map(|chunk| chunk.map(web::Bytes::from));
+    Ok(response.streaming(body))
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    format: Option<String>,
+    mode: Option<String>,
+    dry_run: Option<String>,
+    #[serde(flatten)]
+    analysis: AnalysisQuery,
+}
+
+/// Imports an export. With `mode=reanalyze` (the default) properties and ids
+/// are recomputed with this server's settings; `mode=trust` keeps the supplied
+/// properties but still checks ids against the values. `dry_run=true` reports
+/// what would happen without writing.
+pub async fn import_strings(
+    store: SharedStore,
+    settings: web::Data<AnalysisSettings>,
+    req: HttpRequest,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    let mut errors = ParamErrors::default();
+    let format = errors.parse("format", query.format.as_deref(), TransferFormat::parse).unwrap_or_default();
+    let mode = errors.parse("mode", query.mode.as_deref(), ImportMode::parse).unwrap_or_default();
+    let dry_run = errors.parse("dry_run", query.dry_run.as_deref(), parse_bool).unwrap_or(false);
+    errors.finish()?;
+    let algorithms = query.analysis.hash_algorithms()?;
+    let records = parse_import(&body, format).map_err(AppError::InvalidBody)?;
+
+    let analysis = settings.clone();
+    let analyzed = analyze_in_parallel(records, move |record| {
+        let record = record?;
+        if record.value.is_empty() {
+            return Err("value field cannot be empty".to_string());
+        }
+        let created_at = record.created_at.unwrap_or_else(Utc::now);
+        match mode {
+            ImportMode::Reanalyze => Ok(StoredString {
+                id: analysis.id_for(&record.value),
+                properties: analysis.analyze(&record.value, &analysis.palindrome_policy, &algorithms),
+                value: record.value,
+                created_at,
+            }),
+            ImportMode::Trust => {
+                let properties = record.properties.ok_or("properties are required with mode=trust")?;
+                if properties.sha256_hash != compute_sha256(&record.value) {
+                    return Err("sha256_hash does not match value".to_string());
+                }
+                // Stored digests are lookup keys, so a forged one would hand
+                // another value's lookups to this string.
+                if let Some(algorithm) = properties
+                    .hashes
+                    .iter()
+                    .find_map(|(algorithm, digest)| (*digest != algorithm.digest(&record.value)).then_some(algorithm))
+                {
+                    return Err(format!("hashes.{} does not match value", algorithm.name()));
+                }
+                let id = analysis.id_for(&record.value);
+                if record.id.is_some_and(|supplied| supplied != id) {
+                    return Err("id does not match value".to_string());
+                }
+                Ok(StoredString {
+                    id,
+                    value: record.value,
+                    properties,
+                    created_at,
+                })
             }
-            return error_response(&e, StatusCode::BAD_REQUEST);
         }
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let (mut results, pending) = classify_items(&store, analyzed)?;
+    if dry_run {
+        for result in results.iter_mut().filter(|r| r.status == BatchItemStatus::Created) {
+            result.status = BatchItemStatus::Valid;
+        }
+    } else {
+        save_pending(&store, &mut results, pending);
+    }
+
+    let response = ImportResponse {
+        imported: count_status(&results, BatchItemStatus::Created) + count_status(&results, BatchItemStatus::Valid),
+        conflicts: count_status(&results, BatchItemStatus::Conflict),
+        invalid: count_status(&results, BatchItemStatus::Invalid),
+        dry_run,
+        results,
     };
+    Ok(success_response(&req, response, StatusCode::OK))
+}
+
+#[derive(Deserialize)]
+pub struct NLQuery {
+    query: String,
+    /// Language of the query; overrides `Accept-Language`.
+    lang: Option<String>,
+}
+
+pub async fn filter_by_natural_language(
+    store: SharedStore,
+    lexicons: web::Data<Lexicons>,
+    req: HttpRequest,
+    query: web::Query<NLQuery>,
+    fields: web::Query<ProjectionQuery>,
+) -> Result<HttpResponse, AppError> {
+    let projection = parse_projection(&fields)?;
+    let lexicon = match query.lang.as_deref() {
+        Some(lang) => lexicons.get(lang).ok_or_else(|| {
+            AppError::invalid_param(
+                "lang",
+                format!("Unsupported language: {}. Use one of: {}", lang, lexicons.locales().join(", ")),
+            )
+        })?,
+        None => req
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .map_or_else(|| lexicons.english(), |accept| lexicons.negotiate(accept)),
+    };
+    let interpreted = interpret_natural_language_in(&query.query, lexicon)?;
     
-    let data = storage::get_all_strings(filters.clone());
+    let pagination = Pagination {
+        sort_by: interpreted.sort_by.unwrap_or_default(),
+        order: interpreted.order.unwrap_or_default(),
+        limit: interpreted.limit,
+        cursor: None,
+    };
+
+    if accepts_ndjson(&req) {
+        let rows = PagedRows::new(store.clone(), interpreted.parsed_filters.clone(), pagination);
+        return Ok(ndjson_response(rows, &projection, move |_, count| StreamSummary {
+            count,
+            filters_applied: interpreted.parsed_filters,
+            next_cursor: None,
+            original: Some(interpreted.original),
+        }));
+    }
+
+    let (data, _) = store.get_page(&interpreted.parsed_filters, &pagination)?;
     let count = data.len();
-    
     let response = NaturalLanguageResponse {
         data,
         count,
-        interpreted_query: InterpretedQuery {
-            original,
-            parsed_filters: filters,
-        },
+        interpreted_query: interpreted,
     };
     
-    success_response(response, StatusCode::OK)
+    Ok(projected_response(&req, response, &projection, StatusCode::OK))
 }
 
-pub async fn delete_string(path: web::Path<String>) -> HttpResponse {
-    let value = path.into_inner();
-    
-    match storage::delete_string(&value) {
-        Ok(_) => HttpResponse::NoContent().finish(),
-        Err(_) => error_response("String not found", StatusCode::NOT_FOUND),
-    }
+pub async fn delete_string(store: SharedStore, path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    store.delete_string(&path.into_inner())?;
+    Ok(HttpResponse::NoContent().finish())
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -114,7 +538,21 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         web::scope("/strings")
             .route("", web::post().to(create_string))
             .route("", web::get().to(get_all_strings))
+            .service(
+                web::resource("/batch")
+                    .guard(guard::Post())
+                    .app_data(web::PayloadConfig::new(MAX_BATCH_BYTES))
+                    .route(web::post().to(create_strings_batch))
+            )
+            .service(
+                web::resource("/import")
+                    .guard(guard::Post())
+                    .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+                    .route(web::post().to(import_strings))
+            )
+            .route("/export", web::get().to(export_strings))
             .route("/filter-by-natural-language", web::get().to(filter_by_natural_language))
+            .route("/by-hash/{digest}", web::get().to(get_string_by_digest))
             .route("/{value}", web::get().to(get_string))
             .route("/{value}", web::delete().to(delete_string))
     );
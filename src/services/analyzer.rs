@@ -1,48 +1,225 @@
-use crate::models::string::StringProperties;
-use sha2::{Sha256, Digest};
-use std::collections::HashMap;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::models::string::{CaseMode, CharClass, PalindromePolicy, StringInput, StringProperties};
+use crate::services::analyzer_registry::AnalyzerRegistry;
+use crate::services::hashing::{compute_hashes, compute_sha256, HashAlgorithm};
+use crate::utils::projection::Projection;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
+static PUNCTUATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\p{P}+$").unwrap());
+
+/// Properties filled from `count_char_classes`.
+const CHAR_CLASS_PROPERTIES: &[&str] = &["vowel_count", "consonant_count", "digit_count", "punctuation_count"];
+
+/// Server-wide analysis settings, shared with the routes through `web::Data`.
+#[derive(Clone)]
+pub struct AnalysisSettings {
+    pub palindrome_policy: PalindromePolicy,
+    pub registry: AnalyzerRegistry,
+    /// Algorithm used to derive `StoredString.id` from the value.
+    pub id_algorithm: HashAlgorithm,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        AnalysisSettings {
+            palindrome_policy: PalindromePolicy::default(),
+            registry: AnalyzerRegistry::with_defaults(),
+            id_algorithm: HashAlgorithm::default(),
+        }
+    }
+}
+
+impl AnalysisSettings {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let registry = match &config.enabled_analyzers {
+            Some(names) => AnalyzerRegistry::with_enabled(names)?,
+            None => AnalyzerRegistry::with_defaults(),
+        };
+        Ok(AnalysisSettings {
+            palindrome_policy: config.palindrome_policy,
+            registry,
+            id_algorithm: config.id_algorithm,
+        })
+    }
+
+    pub fn id_for(&self, value: &str) -> String {
+        self.id_algorithm.digest(value)
+    }
+
+    /// Computes the built-in properties, runs every registered analyzer and
+    /// adds the requested digests. The id digest is always included.
+    pub fn analyze(&self, value: &str, policy: &PalindromePolicy, hashes: &[HashAlgorithm]) -> StringProperties {
+        self.analyze_projected(value, policy, hashes, &Projection::default())
+    }
+
+    /// Like `analyze`, but skips the properties `projection` drops. Those are
+    /// left at their defaults, so the result is only fit for projected output.
+    pub fn analyze_projected(
+        &self,
+        value: &str,
+        policy: &PalindromePolicy,
+        hashes: &[HashAlgorithm],
+        projection: &Projection,
+    ) -> StringProperties {
+        let mut properties = analyze_string_selected(value, policy, |name| projection.wants_property(name));
+        for analyzer in self.registry.iter().filter(|a| projection.wants_property(a.name())) {
+            properties.extra.insert(analyzer.name().to_string(), analyzer.analyze(value));
+        }
+        if projection.wants_property("hashes") {
+            properties.hashes = compute_hashes(value, hashes);
+            properties
+                .hashes
+                .entry(self.id_algorithm)
+                .or_insert_with(|| self.id_algorithm.digest(value));
+        }
+        properties
+    }
+
+    /// Analyzes a request body, applying its palindrome policy override on top
+    /// of the server policy.
+    pub fn analyze_input(&self, input: &StringInput, hashes: &[HashAlgorithm]) -> Result<StringProperties, AppError> {
+        self.analyze_input_projected(input, hashes, &Projection::default())
+    }
+
+    pub fn analyze_input_projected(
+        &self,
+        input: &StringInput,
+        hashes: &[HashAlgorithm],
+        projection: &Projection,
+    ) -> Result<StringProperties, AppError> {
+        if input.value.is_empty() {
+            return Err(AppError::invalid_param("value", "value field cannot be empty"));
+        }
+        let policy = input.palindrome_policy.apply(self.palindrome_policy);
+        Ok(self.analyze_projected(&input.value, &policy, hashes, projection))
+    }
+}
+
+/// Analyzes `value` with the default palindrome policy.
 pub fn analyze_string(value: &str) -> StringProperties {
-    let length = value.len();
-    let is_palindrome = check_palindrome(value);
-    let unique_characters = count_unique_characters(value);
+    analyze_string_with_policy(value, &PalindromePolicy::default())
+}
+
+/// Analyzes `value`. Character-level properties (length, uniqueness,
+/// frequencies, palindromes) are measured in extended grapheme clusters, so a
+/// base letter with combining marks or a ZWJ emoji sequence counts as one unit.
+pub fn analyze_string_with_policy(value: &str, policy: &PalindromePolicy) -> StringProperties {
+    analyze_string_selected(value, policy, |_| true)
+}
+
+/// Analyzes `value`, computing the costlier properties only when `wants`
+/// returns true for their name. Skipped ones keep their default value.
+pub fn analyze_string_selected(
+    value: &str,
+    policy: &PalindromePolicy,
+    wants: impl Fn(&str) -> bool,
+) -> StringProperties {
+    let graphemes: Vec<&str> = value.graphemes(true).collect();
+
+    let grapheme_count = graphemes.len();
+    let is_palindrome = wants("is_palindrome") && check_palindrome(value, policy);
+    let unique_characters = if wants("unique_characters") { count_unique_characters(&graphemes) } else { 0 };
     let word_count = count_words(value);
-    let sha256_hash = compute_sha256(value);
-    let character_frequency_map = build_frequency_map(value);
+    let class_counts = if CHAR_CLASS_PROPERTIES.iter().any(|name| wants(name)) {
+        count_char_classes(&graphemes)
+    } else {
+        HashMap::new()
+    };
+    let class_count = |class| class_counts.get(&class).copied().unwrap_or(0);
+    let sha256_hash = if wants("sha256_hash") { compute_sha256(value) } else { String::new() };
+    let character_frequency_map = if wants("character_frequency_map") {
+        build_frequency_map(&graphemes)
+    } else {
+        HashMap::new()
+    };
 
     StringProperties {
-        length,
+        length: grapheme_count,
+        byte_length: value.len(),
+        code_point_count: value.chars().count(),
+        grapheme_count,
         is_palindrome,
+        palindrome_policy: *policy,
         unique_characters,
         word_count,
+        vowel_count: class_count(CharClass::Vowel),
+        consonant_count: class_count(CharClass::Consonant),
+        digit_count: class_count(CharClass::Digit),
+        punctuation_count: class_count(CharClass::Punctuation),
         sha256_hash,
         character_frequency_map,
+        hashes: Default::default(),
+        extra: Default::default(),
     }
 }
 
-fn check_palindrome(s: &str) -> bool {
-    let cleaned: String = s.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
-    cleaned == cleaned.chars().rev().collect::<String>()
+fn check_palindrome(s: &str, policy: &PalindromePolicy) -> bool {
+    let normalized = normalize_for_palindrome(s, policy);
+    let cleaned: Vec<&str> = normalized
+        .graphemes(true)
+        .filter(|g| !(policy.ignore_whitespace && g.chars().all(char::is_whitespace)))
+        .filter(|g| !(policy.ignore_punctuation && PUNCTUATION.is_match(g)))
+        .collect();
+    cleaned.iter().eq(cleaned.iter().rev())
 }
 
-fn count_unique_characters(s: &str) -> usize {
-    s.chars().collect::<std::collections::HashSet<_>>().len()
+fn normalize_for_palindrome(s: &str, policy: &PalindromePolicy) -> String {
+    let s: String = if policy.ignore_diacritics {
+        s.nfd().filter(|c| !is_combining_mark(*c)).collect()
+    } else {
+        s.to_string()
+    };
+    match policy.case_mode {
+        CaseMode::Sensitive => s,
+        CaseMode::Lowercase => s.to_lowercase(),
+        // Unicode default case folding, including expansions such as `ß` -> `ss`.
+        CaseMode::Fold => caseless::default_case_fold_str(&s),
+    }
+}
+
+fn count_unique_characters(graphemes: &[&str]) -> usize {
+    graphemes.iter().collect::<HashSet<_>>().len()
 }
 
 fn count_words(s: &str) -> usize {
     s.split_whitespace().count()
 }
 
-fn compute_sha256(s: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(s.as_bytes());
-    format!("{:x}", hasher.finalize())
+/// The class of a grapheme cluster, judged by its first code point with any
+/// diacritics removed, so "É" is a vowel.
+pub fn char_class(grapheme: &str) -> Option<CharClass> {
+    if PUNCTUATION.is_match(grapheme) {
+        return Some(CharClass::Punctuation);
+    }
+    let first = grapheme.chars().next()?;
+    if first.is_numeric() {
+        return Some(CharClass::Digit);
+    }
+    match std::iter::once(first).nfd().next()?.to_ascii_lowercase() {
+        'a' | 'e' | 'i' | 'o' | 'u' => Some(CharClass::Vowel),
+        c if c.is_ascii_alphabetic() => Some(CharClass::Consonant),
+        _ => None,
+    }
+}
+
+fn count_char_classes(graphemes: &[&str]) -> HashMap<CharClass, usize> {
+    let mut counts = HashMap::new();
+    for class in graphemes.iter().filter_map(|g| char_class(g)) {
+        *counts.entry(class).or_insert(0) += 1;
+    }
+    counts
 }
 
-fn build_frequency_map(s: &str) -> HashMap<char, usize> {
+fn build_frequency_map(graphemes: &[&str]) -> HashMap<String, usize> {
     let mut map = HashMap::new();
-    for ch in s.chars() {
-        *map.entry(ch).or_insert(0) += 1;
+    for g in graphemes {
+        *map.entry(g.to_string()).or_insert(0) += 1;
     }
     map
-}
\ No newline at end of file
+}
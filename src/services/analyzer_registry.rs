@@ -0,0 +1,185 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Names of the properties `analyze_string` always computes. Custom analyzers
+/// may not reuse them, since their output shares the same JSON object.
+pub const BUILTIN_PROPERTIES: &[&str] = &[
+    "length",
+    "byte_length",
+    "code_point_count",
+    "grapheme_count",
+    "is_palindrome",
+    "palindrome_policy",
+    "unique_characters",
+    "word_count",
+    "vowel_count",
+    "consonant_count",
+    "digit_count",
+    "punctuation_count",
+    "sha256_hash",
+    "hashes",
+    "character_frequency_map",
+];
+
+/// A pluggable string metric. Its result is stored under `name()` in the
+/// string's properties.
+pub trait Analyzer: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn version(&self) -> &str;
+
+    fn description(&self) -> &str {
+        ""
+    }
+
+    fn analyze(&self, value: &str) -> Value;
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalyzerInfo {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub builtin: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct AnalyzerRegistry {
+    analyzers: Vec<Arc<dyn Analyzer>>,
+}
+
+impl AnalyzerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with every analyzer that ships with the service.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for analyzer in default_analyzers() {
+            registry.register(analyzer).expect("default analyzer names are unique");
+        }
+        registry
+    }
+
+    /// Registry with only the named default analyzers.
+    pub fn with_enabled(names: &[String]) -> Result<Self, String> {
+        let mut available: HashMap<String, Arc<dyn Analyzer>> = default_analyzers()
+            .into_iter()
+            .map(|a| (a.name().to_string(), a))
+            .collect();
+        let mut registry = Self::new();
+        for name in names {
+            let analyzer = available
+                .remove(name)
+                .ok_or_else(|| format!("Unknown analyzer: {}", name))?;
+            registry.register(analyzer)?;
+        }
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, analyzer: Arc<dyn Analyzer>) -> Result<(), String> {
+        let name = analyzer.name();
+        if BUILTIN_PROPERTIES.contains(&name) {
+            return Err(format!("Analyzer name {} collides with a built-in property", name));
+        }
+        if self.analyzers.iter().any(|a| a.name() == name) {
+            return Err(format!("Analyzer {} is already registered", name));
+        }
+        self.analyzers.push(analyzer);
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Analyzer>> {
+        self.analyzers.iter()
+    }
+
+    pub fn list(&self) -> Vec<AnalyzerInfo> {
+        let mut infos = vec![AnalyzerInfo {
+            name: "core".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: format!("Built-in properties: {}", BUILTIN_PROPERTIES.join(", ")),
+            builtin: true,
+        }];
+        infos.extend(self.analyzers.iter().map(|a| AnalyzerInfo {
+            name: a.name().to_string(),
+            version: a.version().to_string(),
+            description: a.description().to_string(),
+            builtin: false,
+        }));
+        infos
+    }
+}
+
+fn default_analyzers() -> Vec<Arc<dyn Analyzer>> {
+    vec![Arc::new(EntropyAnalyzer), Arc::new(AverageWordLengthAnalyzer)]
+}
+
+/// Shannon entropy in bits per grapheme cluster.
+pub struct EntropyAnalyzer;
+
+impl Analyzer for EntropyAnalyzer {
+    fn name(&self) -> &str {
+        "entropy"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "Shannon entropy in bits per grapheme cluster"
+    }
+
+    fn analyze(&self, value: &str) -> Value {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut total = 0;
+        for g in value.graphemes(true) {
+            *counts.entry(g).or_insert(0) += 1;
+            total += 1;
+        }
+        if total == 0 {
+            return json!(0.0);
+        }
+        let entropy: f64 = counts
+            .values()
+            .map(|&n| {
+                let p = n as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum();
+        json!((entropy * 1000.0).round() / 1000.0)
+    }
+}
+
+/// Mean number of grapheme clusters per whitespace-separated word.
+pub struct AverageWordLengthAnalyzer;
+
+impl Analyzer for AverageWordLengthAnalyzer {
+    fn name(&self) -> &str {
+        "average_word_length"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "Mean number of grapheme clusters per word"
+    }
+
+    fn analyze(&self, value: &str) -> Value {
+        let lengths: Vec<usize> = value
+            .split_whitespace()
+            .map(|w| w.graphemes(true).count())
+            .collect();
+        if lengths.is_empty() {
+            return json!(0.0);
+        }
+        let mean = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+        json!((mean * 1000.0).round() / 1000.0)
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha1,
+    Sha512,
+    Blake3,
+    /// XXH3, 64-bit.
+    Xxhash,
+    Crc32,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 6] = [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha1,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Blake3,
+        HashAlgorithm::Xxhash,
+        HashAlgorithm::Crc32,
+    ];
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "xxhash" | "xxh3" => Ok(HashAlgorithm::Xxhash),
+            "crc32" => Ok(HashAlgorithm::Crc32),
+            _ => Err(format!("Unsupported hash algorithm: {}", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Xxhash => "xxhash",
+            HashAlgorithm::Crc32 => "crc32",
+        }
+    }
+
+    /// Whether collisions are infeasible to find, which ids rely on.
+    pub fn is_cryptographic(self) -> bool {
+        !matches!(self, HashAlgorithm::Xxhash | HashAlgorithm::Crc32)
+    }
+
+    /// Lowercase hex digest of the UTF-8 bytes of `s`.
+    pub fn digest(self, s: &str) -> String {
+        let bytes = s.as_bytes();
+        match self {
+            HashAlgorithm::Sha256 => format!("{:x}", Sha256::digest(bytes)),
+            HashAlgorithm::Sha1 => format!("{:x}", Sha1::digest(bytes)),
+            HashAlgorithm::Sha512 => format!("{:x}", Sha512::digest(bytes)),
+            HashAlgorithm::Blake3 => blake3::hash(bytes).to_hex().to_string(),
+            HashAlgorithm::Xxhash => format!("{:016x}", xxhash_rust::xxh3::xxh3_64(bytes)),
+            HashAlgorithm::Crc32 => format!("{:08x}", crc32fast::hash(bytes)),
+        }
+    }
+}
+
+pub fn compute_sha256(s: &str) -> String {
+    HashAlgorithm::Sha256.digest(s)
+}
+
+/// Parses a comma-separated list such as `blake3,sha512`, dropping duplicates.
+pub fn parse_algorithms(list: &str) -> Result<Vec<HashAlgorithm>, String> {
+    let mut algorithms = Vec::new();
+    for name in list.split(',').filter(|name| !name.trim().is_empty()) {
+        let algorithm = HashAlgorithm::parse(name)?;
+        if !algorithms.contains(&algorithm) {
+            algorithms.push(algorithm);
+        }
+    }
+    Ok(algorithms)
+}
+
+pub fn compute_hashes(value: &str, algorithms: &[HashAlgorithm]) -> BTreeMap<HashAlgorithm, String> {
+    algorithms.iter().map(|alg| (*alg, alg.digest(value))).collect()
+}
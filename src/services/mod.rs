@@ -1,3 +1,6 @@
 pub mod analyzer;
+pub mod analyzer_registry;
+pub mod hashing;
 pub mod storage;
+pub mod transfer;
 pub mod nlp_parser;
\ No newline at end of file
@@ -0,0 +1,865 @@
+use super::tokenizer::{Token, TokenKind};
+use crate::models::string::{CharClass, ContainsMode, SortKey, SortOrder};
+use std::ops::Range;
+
+/// Turn the clause that follows them around: "not palindromes", "no more
+/// than 5 words".
+const NEGATIONS: &[&str] = &["not", "no", "non", "never", "isn't", "aren't", "don't", "doesn't", "excluding", "except"];
+
+/// Words that carry no meaning of their own. They are skipped silently rather
+/// than reported as ignored.
+const FILLERS: &[&str] = &[
+    "a", "an", "the", "all", "any", "every", "either", "and", "but", "also", "plus", "that", "which", "who",
+    "whose", "where", "are", "is", "be", "being", "was", "were", "with", "has", "have", "having", "of", "in",
+    "strings", "string", "values", "value", "entries", "ones", "items", "long", "length", "me", "show", "find",
+    "list", "get", "give", "return", "those", "than", "made", "consisting", "composed",
+];
+
+/// Verbs that introduce what a string contains. Only the strong ones may be
+/// followed by a bare letter, so "with a single word" is not read as "with the
+/// letter a".
+const STRONG_CONTAINS: &[&str] = &["containing", "contains", "contain", "including", "includes", "include"];
+const WEAK_CONTAINS: &[&str] = &["with", "has", "have", "having"];
+
+const CHAR_UNITS: &[&str] = &["character", "characters", "char", "chars", "letter", "letters", "symbol", "symbols"];
+const WORD_UNITS: &[&str] = &["word", "words"];
+
+const CLASS_NOUNS: &[(&[&str], CharClass)] = &[
+    (&["vowel", "vowels"], CharClass::Vowel),
+    (&["consonant", "consonants"], CharClass::Consonant),
+    (&["digit", "digits", "numeral", "numerals"], CharClass::Digit),
+    (&["punctuation"], CharClass::Punctuation),
+];
+
+/// "the second vowel" is a letter, counting the vowels a, e, i, o, u in that
+/// order, so "the last vowel" is u. Since that reading is a convention, "last"
+/// and "final" also offer the string ending in a vowel as an alternative.
+const ORDINAL_VOWELS: &[(&str, char)] = &[
+    ("first", 'a'),
+    ("second", 'e'),
+    ("third", 'i'),
+    ("fourth", 'o'),
+    ("fifth", 'u'),
+    ("last", 'u'),
+    ("final", 'u'),
+];
+
+/// Numeric properties a query can bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    Length,
+    WordCount,
+    UniqueCharacters,
+    /// Characters of one class: "3 vowels", "no digits".
+    Count(CharClass),
+}
+
+/// Inclusive bounds on a `Measure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bounds {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl Bounds {
+    pub fn intersect(self, other: Bounds) -> Bounds {
+        Bounds {
+            min: self.min.max(other.min),
+            max: match (self.max, other.max) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        matches!((self.min, self.max), (Some(min), Some(max)) if min > max)
+    }
+
+    /// The complement, when it is a single range.
+    fn negate(self) -> Option<Bounds> {
+        match (self.min, self.max) {
+            (Some(min), None) => min.checked_sub(1).map(|max| Bounds { min: None, max: Some(max) }),
+            (None, Some(max)) => max.checked_add(1).map(|min| Bounds { min: Some(min), max: None }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+impl Comparison {
+    fn bounds(self, n: usize) -> Bounds {
+        match self {
+            Comparison::Gt => Bounds { min: Some(n.saturating_add(1)), max: None },
+            Comparison::Ge => Bounds { min: Some(n), max: None },
+            // Nothing is shorter than 0; the empty range is reported as a conflict.
+            Comparison::Lt => match n.checked_sub(1) {
+                Some(max) => Bounds { min: None, max: Some(max) },
+                None => Bounds { min: Some(1), max: Some(0) },
+            },
+            Comparison::Le => Bounds { min: None, max: Some(n) },
+            Comparison::Eq => Bounds { min: Some(n), max: Some(n) },
+        }
+    }
+}
+
+/// Phrases that open a comparison, each optionally followed by "than". They
+/// do not name a measure, so a missing unit has to be guessed.
+const COMPARATIVES: &[(&[&str], Comparison)] = &[
+    (&["more"], Comparison::Gt),
+    (&["over"], Comparison::Gt),
+    (&["above"], Comparison::Gt),
+    (&["greater"], Comparison::Gt),
+    (&["exceeding"], Comparison::Gt),
+    (&["fewer"], Comparison::Lt),
+    (&["less"], Comparison::Lt),
+    (&["under"], Comparison::Lt),
+    (&["below"], Comparison::Lt),
+    (&["at", "least"], Comparison::Ge),
+    (&["minimum"], Comparison::Ge),
+    (&["min"], Comparison::Ge),
+    (&["at", "most"], Comparison::Le),
+    (&["maximum"], Comparison::Le),
+    (&["max"], Comparison::Le),
+    (&["up", "to"], Comparison::Le),
+    (&["exactly"], Comparison::Eq),
+    (&["precisely"], Comparison::Eq),
+];
+
+/// Comparisons about length even when no unit follows.
+const LENGTH_COMPARATIVES: &[(&[&str], Comparison)] = &[
+    (&["longer"], Comparison::Gt),
+    (&["shorter"], Comparison::Lt),
+    (&["length", "of"], Comparison::Eq),
+    (&["length", "is"], Comparison::Eq),
+    (&["length"], Comparison::Eq),
+];
+
+/// Words that sort the results by themselves.
+const SUPERLATIVES: &[(&[&str], SortKey, SortOrder)] = &[
+    (&["longest"], SortKey::Length, SortOrder::Desc),
+    (&["shortest"], SortKey::Length, SortOrder::Asc),
+    (&["wordiest"], SortKey::WordCount, SortOrder::Desc),
+    (&["newest"], SortKey::CreatedAt, SortOrder::Desc),
+    (&["latest"], SortKey::CreatedAt, SortOrder::Desc),
+    (&["most", "recent"], SortKey::CreatedAt, SortOrder::Desc),
+    (&["oldest"], SortKey::CreatedAt, SortOrder::Asc),
+    (&["earliest"], SortKey::CreatedAt, SortOrder::Asc),
+];
+
+/// Confidence of a clause that needed a guess, for the likelier and the less
+/// likely reading.
+const GUESS: f64 = 0.8;
+const ALTERNATIVE_GUESS: f64 = 0.5;
+
+/// How to settle the points where a query can be read more than one way. The
+/// default is the likelier reading; each flag picks the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Choices {
+    /// A comparison without a unit, "more than 3", counts words, not characters.
+    pub unitless_words: bool,
+    /// "containing a z" means the letter a, not the article.
+    pub article_as_letter: bool,
+    /// "or" binds tighter than "and": "palindromes longer than 5 or shorter
+    /// than 2" keeps "palindromes" on both sides.
+    pub tight_or: bool,
+    /// "the last vowel" is whatever vowel a string ends with, not the letter u.
+    pub last_vowel_at_end: bool,
+}
+
+impl Choices {
+    /// Readings that differ from `self` in one of the points `consulted` by a parse.
+    pub fn alternatives(self, consulted: Choices) -> Vec<Choices> {
+        let mut alternatives = Vec::new();
+        if consulted.unitless_words {
+            alternatives.push(Choices { unitless_words: !self.unitless_words, ..self });
+        }
+        if consulted.article_as_letter {
+            alternatives.push(Choices { article_as_letter: !self.article_as_letter, ..self });
+        }
+        if consulted.tight_or {
+            alternatives.push(Choices { tight_or: !self.tight_or, ..self });
+        }
+        if consulted.last_vowel_at_end {
+            alternatives.push(Choices { last_vowel_at_end: !self.last_vowel_at_end, ..self });
+        }
+        alternatives
+    }
+}
+
+/// How the results are sorted and cut, from phrases such as "the 5 longest"
+/// or "sorted by word count".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResultOrder {
+    pub sort: Option<(SortKey, SortOrder)>,
+    pub limit: Option<usize>,
+}
+
+/// A top-level clause and the tokens it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub node: Node,
+    pub tokens: Range<usize>,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Palindrome(bool),
+    Range(Measure, Bounds),
+    ContainsChars(Vec<char>, ContainsMode),
+    Contains(String),
+    StartsWith(String),
+    EndsWith(String),
+    StartsWithClass(CharClass),
+    EndsWithClass(CharClass),
+    /// "only consonants": some of the class and no letters or digits of
+    /// another class.
+    Only(CharClass),
+    /// Not a condition on its own; switches text matching to case-insensitive.
+    CaseInsensitive,
+}
+
+impl Condition {
+    /// The negated condition, when the filter model can state it directly.
+    pub fn negate(&self) -> Option<Condition> {
+        match self {
+            Condition::Palindrome(b) => Some(Condition::Palindrome(!b)),
+            Condition::Range(measure, bounds) => bounds.negate().map(|b| Condition::Range(*measure, b)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Condition(Condition),
+    Not(Box<Node>),
+    And(Vec<Node>),
+    Or(Vec<Node>),
+}
+
+fn number_word(word: &str) -> Option<usize> {
+    let n = match word {
+        "zero" | "none" => 0,
+        "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" | "dozen" => 12,
+        "thirteen" => 13,
+        "fourteen" => 14,
+        "fifteen" => 15,
+        "sixteen" => 16,
+        "seventeen" => 17,
+        "eighteen" => 18,
+        "nineteen" => 19,
+        "twenty" => 20,
+        "thirty" => 30,
+        "forty" => 40,
+        "fifty" => 50,
+        "sixty" => 60,
+        "seventy" => 70,
+        "eighty" => 80,
+        "ninety" => 90,
+        "hundred" => 100,
+        _ => return None,
+    };
+    Some(n)
+}
+
+/// Recursive-descent parser over the grammar
+///
+/// ```text
+/// query     := and_list ("or" and_list)*
+/// and_list  := (unary | filler)*
+/// unary     := negation unary | condition
+/// condition := palindrome | comparison | contains | affix | case | ...
+/// ```
+///
+/// Words no rule accepts are skipped, and recorded in `ignored` unless they
+/// are filler.
+pub struct Parser<'a> {
+    /// The query the tokens were read from, which literal text is cut out of.
+    chars: &'a [char],
+    tokens: &'a [Token],
+    pos: usize,
+    choices: Choices,
+    /// The ambiguous points this parse ran into.
+    pub consulted: Choices,
+    pub clauses: Vec<Clause>,
+    /// Sorting and limiting phrases, in query order.
+    pub orderings: Vec<(ResultOrder, Range<usize>)>,
+    /// Indices into the token list.
+    pub ignored: Vec<usize>,
+    /// Confidence in how the clauses were grouped by "and" and "or".
+    pub structure_confidence: f64,
+    /// Confidence of the clause being parsed.
+    confidence: f64,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(chars: &'a [char], tokens: &'a [Token], choices: Choices) -> Self {
+        Parser {
+            chars,
+            tokens,
+            pos: 0,
+            choices,
+            consulted: Choices::default(),
+            clauses: Vec::new(),
+            orderings: Vec::new(),
+            ignored: Vec::new(),
+            structure_confidence: 1.0,
+            confidence: 1.0,
+        }
+    }
+
+    /// The whole query as one tree, or `None` if no clause was recognized.
+    pub fn parse(&mut self) -> Option<Node> {
+        self.parse_or()
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_word(&self, offset: usize, words: &[&str]) -> bool {
+        self.tokens
+            .get(self.pos + offset)
+            .and_then(Token::word)
+            .is_some_and(|w| words.contains(&w))
+    }
+
+    fn eat(&mut self, words: &[&str]) -> bool {
+        let matched = self.is_word(0, words);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn eat_seq(&mut self, seq: &[&str]) -> bool {
+        let matched = seq.iter().enumerate().all(|(i, word)| self.is_word(i, &[word]));
+        if matched {
+            self.pos += seq.len();
+        }
+        matched
+    }
+
+    /// Runs `rule`, rewinding if it does not match.
+    fn attempt<T>(&mut self, rule: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let start = self.pos;
+        let result = rule(self);
+        if result.is_none() {
+            self.pos = start;
+        }
+        result
+    }
+
+    /// Settles an ambiguous point, lowering the confidence of the clause.
+    fn guess(&mut self, alternative: bool) -> bool {
+        self.confidence *= if alternative { ALTERNATIVE_GUESS } else { GUESS };
+        alternative
+    }
+
+    fn parse_or(&mut self) -> Option<Node> {
+        let mut branches = Vec::new();
+        loop {
+            branches.extend(self.parse_and());
+            if !self.eat(&["or"]) {
+                break;
+            }
+        }
+        if branches.len() > 1 && branches.iter().any(|b| matches!(b, Node::And(_))) {
+            self.consulted.tight_or = true;
+            self.structure_confidence *= GUESS;
+        }
+        match branches.len() {
+            0 => None,
+            1 => branches.pop(),
+            _ => Some(Node::Or(branches)),
+        }
+    }
+
+    /// With `tight_or`, joins the clauses around each "or" before any "and".
+    fn or_chain(&mut self, first: Node) -> Node {
+        let mut branches = vec![first];
+        while let Some(node) = self.attempt(|p| {
+            p.eat(&["or"]).then_some(())?;
+            while p.is_word(0, FILLERS) && !p.is_word(0, &["or"]) {
+                p.pos += 1;
+            }
+            p.parse_unary()
+        }) {
+            branches.push(node);
+        }
+        if branches.len() > 1 {
+            self.consulted.tight_or = true;
+            self.structure_confidence *= ALTERNATIVE_GUESS;
+            Node::Or(branches)
+        } else {
+            branches.pop().expect("starts with one branch")
+        }
+    }
+
+    fn parse_and(&mut self) -> Option<Node> {
+        let mut terms = Vec::new();
+        while let Some(token) = self.peek() {
+            if token.word() == Some("or") {
+                break;
+            }
+            let start = self.pos;
+            if let Some(order) = self.attempt(Self::ordering) {
+                self.orderings.push((order, start..self.pos));
+                continue;
+            }
+            self.confidence = 1.0;
+            if let Some(node) = self.parse_unary() {
+                let node = if self.choices.tight_or { self.or_chain(node) } else { node };
+                self.clauses.push(Clause {
+                    node: node.clone(),
+                    tokens: start..self.pos,
+                    confidence: self.confidence,
+                });
+                terms.push(node);
+                continue;
+            }
+            let filler = token.kind == TokenKind::Comma || token.word().is_some_and(|w| FILLERS.contains(&w));
+            if !filler {
+                self.ignored.push(self.pos);
+            }
+            self.pos += 1;
+        }
+        match terms.len() {
+            0 => None,
+            1 => terms.pop(),
+            _ => Some(Node::And(terms)),
+        }
+    }
+
+    /// "the 5 longest", "top 10", "with the most words", "sorted by length
+    /// descending", "limit 20"
+    fn ordering(&mut self) -> Option<ResultOrder> {
+        if self.eat(&["sorted", "sort", "ordered", "order", "ranked"]) {
+            self.eat(&["by"]).then_some(())?;
+            let key = self.sort_key()?;
+            let order = if self.eat(&["descending", "desc", "decreasing"]) {
+                SortOrder::Desc
+            } else {
+                self.eat(&["ascending", "asc", "increasing"]);
+                SortOrder::Asc
+            };
+            return Some(ResultOrder { sort: Some((key, order)), limit: None });
+        }
+        if self.eat(&["limit", "limited"]) {
+            self.eat(&["to"]);
+            return Some(ResultOrder { sort: None, limit: Some(self.number()?) });
+        }
+        let counted = self.eat(&["top", "first"]);
+        let limit = self.number();
+        let sort = self.superlative();
+        if sort.is_none() && !(counted && limit.is_some()) {
+            return None;
+        }
+        Some(ResultOrder { sort, limit })
+    }
+
+    /// "longest", "newest", "most words", "fewest unique characters"
+    fn superlative(&mut self) -> Option<(SortKey, SortOrder)> {
+        if let Some(sort) = SUPERLATIVES
+            .iter()
+            .find_map(|(phrase, key, order)| self.eat_seq(phrase).then_some((*key, *order)))
+        {
+            return Some(sort);
+        }
+        self.attempt(|p| {
+            let order = if p.eat(&["most"]) {
+                SortOrder::Desc
+            } else if p.eat(&["fewest", "least"]) {
+                SortOrder::Asc
+            } else {
+                return None;
+            };
+            let key = match p.unit()? {
+                Measure::Length => SortKey::Length,
+                Measure::WordCount => SortKey::WordCount,
+                Measure::UniqueCharacters => SortKey::UniqueCharacters,
+                Measure::Count(_) => return None,
+            };
+            Some((key, order))
+        })
+    }
+
+    /// What follows "sorted by": "length", "word count", "date added".
+    fn sort_key(&mut self) -> Option<SortKey> {
+        if self.eat(&["length", "size"]) {
+            return Some(SortKey::Length);
+        }
+        if self.eat_seq(&["word", "count"]) || self.eat(WORD_UNITS) {
+            return Some(SortKey::WordCount);
+        }
+        if self.eat(&["date", "age", "time", "creation", "created"]) {
+            self.eat(&["added", "created", "date", "time"]);
+            return Some(SortKey::CreatedAt);
+        }
+        match self.unit()? {
+            Measure::Length => Some(SortKey::Length),
+            Measure::WordCount => Some(SortKey::WordCount),
+            Measure::UniqueCharacters => Some(SortKey::UniqueCharacters),
+            Measure::Count(_) => None,
+        }
+    }
+
+    fn parse_unary(&mut self) -> Option<Node> {
+        self.attempt(|p| {
+            if p.eat(NEGATIONS) {
+                while p.eat(&["a", "an", "the", "be"]) {}
+                return p.parse_unary().map(|node| Node::Not(Box::new(node)));
+            }
+            p.parse_condition()
+        })
+    }
+
+    fn parse_condition(&mut self) -> Option<Node> {
+        if let Some(condition) = self.attempt(|p| p.eat(&["without"]).then(|| p.contains_target(true)).flatten()) {
+            return Some(Node::Not(Box::new(Node::Condition(condition))));
+        }
+        let rules: [fn(&mut Self) -> Option<Condition>; 6] = [
+            Self::palindrome,
+            Self::case_insensitive,
+            Self::comparison,
+            Self::contains,
+            Self::affix,
+            Self::character_class,
+        ];
+        rules.into_iter().find_map(|rule| self.attempt(rule)).map(Node::Condition)
+    }
+
+    fn palindrome(&mut self) -> Option<Condition> {
+        self.eat(&["palindrome", "palindromes", "palindromic"])
+            .then_some(Condition::Palindrome(true))
+    }
+
+    fn case_insensitive(&mut self) -> Option<Condition> {
+        let phrases: [&[&str]; 6] = [
+            &["case", "insensitive"],
+            &["case", "insensitively"],
+            &["ignoring", "case"],
+            &["ignore", "case"],
+            &["any", "case"],
+            &["regardless", "of", "case"],
+        ];
+        phrases
+            .iter()
+            .any(|phrase| self.eat_seq(phrase))
+            .then_some(Condition::CaseInsensitive)
+    }
+
+    /// "only consonants", "the last vowel", "digits"
+    fn character_class(&mut self) -> Option<Condition> {
+        if self.eat(&["only", "just", "solely", "entirely", "purely"]) {
+            self.eat(&["of"]);
+            return self.class_noun().map(Condition::Only);
+        }
+        self.class_target()
+    }
+
+    /// "vowel", "digits", "punctuation marks"
+    fn class_noun(&mut self) -> Option<CharClass> {
+        let class = CLASS_NOUNS
+            .iter()
+            .find_map(|(words, class)| self.eat(words).then_some(*class))?;
+        if class == CharClass::Punctuation {
+            self.eat(&["mark", "marks", "character", "characters"]);
+        }
+        Some(class)
+    }
+
+    /// A character class as something a string contains: "the second vowel"
+    /// is one letter, "a vowel" or "digits" at least one of the class.
+    fn class_target(&mut self) -> Option<Condition> {
+        self.attempt(|p| {
+            p.eat(&["the"]);
+            if let Some(&(word, letter)) = ORDINAL_VOWELS.iter().find(|(word, _)| p.is_word(0, &[word])) {
+                p.pos += 1;
+                if !p.eat(&["vowel"]) {
+                    return None;
+                }
+                if ["last", "final"].contains(&word) {
+                    p.consulted.last_vowel_at_end = true;
+                    if p.guess(p.choices.last_vowel_at_end) {
+                        return Some(Condition::EndsWithClass(CharClass::Vowel));
+                    }
+                }
+                return Some(Condition::ContainsChars(vec![letter], ContainsMode::All));
+            }
+            p.eat(&["a", "an", "any", "some"]);
+            let class = p.class_noun()?;
+            Some(Condition::Range(Measure::Count(class), Comparison::Ge.bounds(1)))
+        })
+    }
+
+    /// A number, in digits or words: "5", "five", "twenty one", "single".
+    fn number(&mut self) -> Option<usize> {
+        let token = self.peek()?;
+        if let TokenKind::Number(n) = token.kind {
+            self.pos += 1;
+            return Some(n);
+        }
+        let word = token.word()?;
+        if word == "single" {
+            self.pos += 1;
+            return Some(1);
+        }
+        let value = number_word(word)?;
+        self.pos += 1;
+        let next = self.peek().and_then(Token::word).and_then(number_word);
+        match next {
+            Some(unit) if (20..100).contains(&value) && (1..10).contains(&unit) => {
+                self.pos += 1;
+                Some(value + unit)
+            }
+            Some(100) if (1..10).contains(&value) => {
+                self.pos += 1;
+                Some(value * 100)
+            }
+            _ => Some(value),
+        }
+    }
+
+    /// The measure named after a number: "characters", "words", "unique
+    /// letters", "vowels".
+    fn unit(&mut self) -> Option<Measure> {
+        self.attempt(|p| {
+            let unique = p.eat(&["unique", "distinct", "different"]);
+            if p.eat(CHAR_UNITS) {
+                Some(if unique { Measure::UniqueCharacters } else { Measure::Length })
+            } else if unique {
+                None
+            } else if p.eat(WORD_UNITS) {
+                Some(Measure::WordCount)
+            } else {
+                p.class_noun().map(Measure::Count)
+            }
+        })
+    }
+
+    /// "or more", "or fewer" after a number or its unit.
+    fn open_end(&mut self) -> Option<Comparison> {
+        if self.eat_seq(&["or", "more"]) || self.eat_seq(&["or", "longer"]) || self.eat_seq(&["or", "above"]) {
+            Some(Comparison::Ge)
+        } else if self.eat_seq(&["or", "fewer"]) || self.eat_seq(&["or", "less"]) || self.eat_seq(&["or", "shorter"]) {
+            Some(Comparison::Le)
+        } else {
+            None
+        }
+    }
+
+    fn comparison(&mut self) -> Option<Condition> {
+        // "between 3 and 5 words", "from 3 to 5 characters"
+        if self.eat(&["between", "from"]) {
+            let low = self.number()?;
+            if !self.eat(&["and", "to"]) {
+                return None;
+            }
+            let high = self.number()?;
+            let measure = self.unit().unwrap_or_else(|| self.guess_measure());
+            let bounds = Bounds { min: Some(low.min(high)), max: Some(low.max(high)) };
+            return Some(Condition::Range(measure, bounds));
+        }
+
+        // "5 characters", "one word", "3 or more words", "3 words or more"
+        if let Some(n) = self.number() {
+            let before = self.open_end();
+            let measure = self.unit()?;
+            let comparison = before.or_else(|| self.open_end()).unwrap_or(Comparison::Eq);
+            return Some(Condition::Range(measure, comparison.bounds(n)));
+        }
+
+        // "multi word", "multiple words"
+        if self.eat(&["multi", "multiple", "several", "many"]) {
+            self.eat(WORD_UNITS).then_some(())?;
+            return Some(Condition::Range(Measure::WordCount, Comparison::Ge.bounds(2)));
+        }
+
+        // "longer than 5", "at least three words", "fewer than 4 unique characters"
+        let find = |p: &mut Self, table: &[(&[&str], Comparison)]| {
+            table.iter().find_map(|(phrase, comparison)| p.eat_seq(phrase).then_some(*comparison))
+        };
+        let (comparison, names_length) = match find(self, LENGTH_COMPARATIVES) {
+            Some(comparison) => (comparison, true),
+            None => (find(self, COMPARATIVES)?, false),
+        };
+        self.eat(&["than", "of"]);
+        let n = self.number()?;
+        let measure = match self.unit() {
+            Some(measure) => measure,
+            None if names_length => Measure::Length,
+            None => self.guess_measure(),
+        };
+        Some(Condition::Range(measure, comparison.bounds(n)))
+    }
+
+    /// The measure of a comparison with no unit: characters, or words when
+    /// `unitless_words` is chosen.
+    fn guess_measure(&mut self) -> Measure {
+        self.consulted.unitless_words = true;
+        if self.guess(self.choices.unitless_words) {
+            Measure::WordCount
+        } else {
+            Measure::Length
+        }
+    }
+
+    fn contains(&mut self) -> Option<Condition> {
+        if self.eat(STRONG_CONTAINS) {
+            self.contains_target(true)
+        } else if self.eat(WEAK_CONTAINS) {
+            self.contains_target(false)
+        } else {
+            None
+        }
+    }
+
+    /// What follows "containing": "the letter x", "the letters a, b or c",
+    /// "the substring 'ab'", "a vowel", or a bare letter after a strong verb.
+    fn contains_target(&mut self, allow_bare: bool) -> Option<Condition> {
+        if let Some(condition) = self.class_target() {
+            return Some(condition);
+        }
+        self.eat(&["the"]);
+        if self.eat(&["letter", "letters", "character", "characters", "char", "chars"]) {
+            return self.letter_list();
+        }
+        if self.eat(&["substring", "text", "sequence", "word"]) {
+            return self.text().map(Condition::Contains);
+        }
+        if let Some(TokenKind::Quoted(text)) = self.peek().map(|t| &t.kind) {
+            self.pos += 1;
+            return Some(Condition::Contains(text.clone()));
+        }
+        if allow_bare {
+            let skipped = self.skip_article();
+            let letters = self.letter_list()?;
+            if skipped {
+                self.guess(false);
+            }
+            return Some(letters);
+        }
+        None
+    }
+
+    /// One letter, or several separated by commas, "and" or "or". Any "or"
+    /// makes the list match any of the letters instead of all of them.
+    fn letter_list(&mut self) -> Option<Condition> {
+        let first = self.peek().and_then(Token::letter)?;
+        self.pos += 1;
+        let mut letters = vec![first];
+        let mut any = false;
+        loop {
+            let before = self.pos;
+            let mut saw_or = false;
+            while let Some(token) = self.peek() {
+                match (&token.kind, token.word()) {
+                    (TokenKind::Comma, _) | (_, Some("and")) => {}
+                    (_, Some("or")) => saw_or = true,
+                    _ => break,
+                }
+                self.pos += 1;
+            }
+            match self.peek().and_then(Token::letter).filter(|_| self.pos > before) {
+                Some(letter) => {
+                    self.pos += 1;
+                    any |= saw_or;
+                    if !letters.contains(&letter) {
+                        letters.push(letter);
+                    }
+                }
+                None => {
+                    self.pos = before;
+                    break;
+                }
+            }
+        }
+        let mode = if any { ContainsMode::Any } else { ContainsMode::All };
+        Some(Condition::ContainsChars(letters, mode))
+    }
+
+    /// "a" and "an" read as articles, not letters, when a word follows them,
+    /// unless `article_as_letter` is chosen. Returns whether one was skipped.
+    fn skip_article(&mut self) -> bool {
+        let next_is_word = self
+            .tokens
+            .get(self.pos + 1)
+            .and_then(Token::word)
+            .is_some_and(|w| !["and", "or"].contains(&w));
+        if !self.is_word(0, &["a", "an"]) || !next_is_word {
+            return false;
+        }
+        if self.is_word(0, &["a"]) {
+            self.consulted.article_as_letter = true;
+            if self.choices.article_as_letter {
+                self.guess(true);
+                return false;
+            }
+        }
+        self.pos += 1;
+        true
+    }
+
+    /// A quoted string or a single word, taken literally. Words are cut from
+    /// the query rather than the lowercased token, so their case is kept.
+    fn text(&mut self) -> Option<String> {
+        let token = self.peek()?;
+        let text = match &token.kind {
+            TokenKind::Quoted(text) => text.clone(),
+            TokenKind::Word(_) | TokenKind::Number(_) => self.chars[token.start..token.end].iter().collect(),
+            TokenKind::Comma => return None,
+        };
+        self.pos += 1;
+        Some(text)
+    }
+
+    /// "starting with x", "ends with the letter z", "beginning with 'ab'",
+    /// "starts with a vowel"
+    fn affix(&mut self) -> Option<Condition> {
+        let starts = if self.eat(&["starting", "starts", "start", "beginning", "begins", "begin", "prefixed"]) {
+            true
+        } else if self.eat(&["ending", "ends", "end", "suffixed"]) {
+            false
+        } else {
+            return None;
+        };
+        if !self.eat(&["with", "by", "in"]) {
+            return None;
+        }
+        let class = self.attempt(|p| {
+            p.eat(&["a", "an", "the", "any"]);
+            p.class_noun()
+        });
+        if let Some(class) = class {
+            return Some(if starts { Condition::StartsWithClass(class) } else { Condition::EndsWithClass(class) });
+        }
+        self.eat(&["the"]);
+        if !self.eat(&["letter", "character", "substring", "prefix", "suffix", "word", "text"]) && self.skip_article() {
+            self.guess(false);
+        }
+        let text = self.text()?;
+        Some(if starts { Condition::StartsWith(text) } else { Condition::EndsWith(text) })
+    }
+}
@@ -0,0 +1,226 @@
+//! Keyword tables for languages other than English. A lexicon rewrites the
+//! words of a query into the English words the grammar reads, so every
+//! language compiles into the same filters. Locales are JSON data files:
+//!
+//! ```json
+//! { "locale": "es", "phrases": { "más largas que": "longer than", "de": "" } }
+//! ```
+//!
+//! Phrases match case- and accent-insensitively, longest first. An empty
+//! translation drops the words. Words a lexicon does not know pass through
+//! unchanged, so numbers, quoted text and English keywords keep working.
+
+use super::tokenizer::{tokenize, Token, TokenKind};
+use crate::config::Config;
+use crate::utils::format::preferred;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+pub const ENGLISH: &str = "en";
+
+const BUILTIN: &[&str] = &[
+    include_str!("lexicons/es.json"),
+    include_str!("lexicons/fr.json"),
+    include_str!("lexicons/de.json"),
+];
+
+/// The English words after which a single letter is the letter itself, since
+/// the grammar expects one there. Spanish "la letra y" is not "the letter
+/// and", "terminan en o" is "ending with o" and "contienen la y" is
+/// "containing the y".
+const LETTER_CONTEXT: &[&str] = &[
+    "letter", "letters", "character", "characters", "the", "with",
+    "containing", "contains", "contain", "including", "includes", "include",
+];
+
+#[derive(Debug, Deserialize)]
+struct LexiconFile {
+    locale: String,
+    phrases: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    locale: String,
+    /// Folded source words, and the English tokens they stand for.
+    phrases: HashMap<Vec<String>, Vec<TokenKind>>,
+    /// Word count of the longest phrase.
+    longest: usize,
+}
+
+impl Lexicon {
+    /// The grammar's own language; it translates nothing.
+    pub fn english() -> Self {
+        Lexicon {
+            locale: ENGLISH.to_string(),
+            phrases: HashMap::new(),
+            longest: 0,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: LexiconFile = serde_json::from_str(json).map_err(|e| format!("Invalid lexicon: {}", e))?;
+        let locale = file.locale.to_lowercase();
+        if locale.is_empty() || !locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid lexicon locale: {:?}", file.locale));
+        }
+
+        let mut lexicon = Lexicon { locale, ..Lexicon::english() };
+        for (phrase, english) in &file.phrases {
+            let words: Option<Vec<String>> = tokenize(phrase).iter().map(|t| t.word().map(fold)).collect();
+            let words = words
+                .filter(|words| !words.is_empty())
+                .ok_or_else(|| format!("Lexicon phrase {:?} must consist of words", phrase))?;
+            let translation = tokenize(english).into_iter().map(|t| t.kind).collect();
+            lexicon.longest = lexicon.longest.max(words.len());
+            lexicon.phrases.insert(words, translation);
+        }
+        Ok(lexicon)
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Adds `other`'s phrases, replacing any this lexicon already has.
+    fn extend(&mut self, other: Lexicon) {
+        self.longest = self.longest.max(other.longest);
+        self.phrases.extend(other.phrases);
+    }
+
+    /// Rewrites `tokens` into English. Each English token keeps the span of the
+    /// words it replaced, so clauses still point into the original query.
+    pub(super) fn translate(&self, tokens: Vec<Token>) -> Vec<Token> {
+        if self.phrases.is_empty() {
+            return tokens;
+        }
+        let mut translated: Vec<Token> = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let after_letter = translated
+                .last()
+                .and_then(Token::word)
+                .is_some_and(|w| LETTER_CONTEXT.contains(&w));
+            let matched = if after_letter && tokens[i].letter().is_some() {
+                None
+            } else {
+                self.longest_match(&tokens[i..])
+            };
+            match matched {
+                Some((len, english)) => {
+                    let (start, end) = (tokens[i].start, tokens[i + len - 1].end);
+                    translated.extend(english.iter().map(|kind| Token { kind: kind.clone(), start, end }));
+                    i += len;
+                }
+                None => {
+                    translated.push(tokens[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        translated
+    }
+
+    fn longest_match(&self, tokens: &[Token]) -> Option<(usize, &[TokenKind])> {
+        let words: Vec<String> = tokens
+            .iter()
+            .take(self.longest)
+            .map_while(|t| t.word().map(fold))
+            .collect();
+        (1..=words.len())
+            .rev()
+            .find_map(|len| self.phrases.get(&words[..len]).map(|english| (len, english.as_slice())))
+    }
+}
+
+/// Lowercased words with diacritics removed, so "palindromos" matches
+/// "palíndromos".
+fn fold(word: &str) -> String {
+    word.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+/// The lexicons a server understands, keyed by locale. English is always
+/// present.
+#[derive(Debug, Clone)]
+pub struct Lexicons {
+    lexicons: BTreeMap<String, Lexicon>,
+}
+
+impl Default for Lexicons {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Lexicons {
+    /// English plus every lexicon that ships with the service.
+    pub fn builtin() -> Self {
+        let mut lexicons = Lexicons { lexicons: BTreeMap::new() };
+        lexicons.add(Lexicon::english());
+        for json in BUILTIN {
+            lexicons.add(Lexicon::from_json(json).expect("built-in lexicons are valid"));
+        }
+        lexicons
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut lexicons = Self::builtin();
+        if let Some(dir) = &config.lexicon_dir {
+            lexicons.load_dir(dir)?;
+        }
+        Ok(lexicons)
+    }
+
+    /// Adds every `*.json` lexicon in `dir`. A file for a locale that is
+    /// already known extends it.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<(), String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let json = fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+            let lexicon = Lexicon::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+            self.add(lexicon);
+        }
+        Ok(())
+    }
+
+    pub fn add(&mut self, lexicon: Lexicon) {
+        match self.lexicons.get_mut(&lexicon.locale) {
+            Some(existing) => existing.extend(lexicon),
+            None => {
+                self.lexicons.insert(lexicon.locale.clone(), lexicon);
+            }
+        }
+    }
+
+    pub fn locales(&self) -> Vec<&str> {
+        self.lexicons.keys().map(String::as_str).collect()
+    }
+
+    /// The lexicon for a language tag. "es-MX" falls back to "es".
+    pub fn get(&self, tag: &str) -> Option<&Lexicon> {
+        let tag = tag.trim().to_lowercase();
+        self.lexicons.get(&tag).or_else(|| {
+            let primary = tag.split(['-', '_']).next()?;
+            self.lexicons.get(primary)
+        })
+    }
+
+    /// Picks the known language with the highest `q` in an `Accept-Language`
+    /// header, falling back to English.
+    pub fn negotiate(&self, accept_language: &str) -> &Lexicon {
+        preferred([accept_language], |tag| self.get(tag)).unwrap_or_else(|| self.english())
+    }
+
+    pub fn english(&self) -> &Lexicon {
+        &self.lexicons[ENGLISH]
+    }
+}
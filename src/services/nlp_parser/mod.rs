@@ -0,0 +1,525 @@
+//! Natural language queries. A query is tokenized, parsed by a small grammar
+//! into a tree of conditions, and compiled into the same `Filters` the
+//! structured listing endpoint uses. Conditions the flat fields cannot state,
+//! such as a negated letter or an "or", become a `filter` expression.
+//!
+//! Where a query can be read more than one way, the likelier reading is used
+//! and the others are returned as alternatives. Queries in other languages
+//! are first rewritten into English by a `Lexicon`.
+
+mod grammar;
+mod lexicon;
+mod tokenizer;
+
+pub use lexicon::{Lexicon, Lexicons};
+
+use crate::error::AppError;
+use crate::models::string::{CharClass, ContainsMode, Filters, InterpretedQuery, Interpretation, QueryClause, QueryWord};
+use crate::utils::filters::expr::{CmpOp, Field, FilterExpr, Literal};
+use crate::utils::pagination::MAX_LIMIT;
+use grammar::{Bounds, Choices, Condition, Measure, Node, Parser, ResultOrder};
+use tokenizer::Token;
+
+pub fn parse_natural_language(query: &str) -> Result<(Filters, String), AppError> {
+    interpret_natural_language(query).map(|interpreted| (interpreted.parsed_filters, interpreted.original))
+}
+
+/// Parses an English `query` and explains the result: which words produced
+/// which filter, which were ignored, how confident the reading is, and other
+/// readings.
+pub fn interpret_natural_language(query: &str) -> Result<InterpretedQuery, AppError> {
+    interpret_natural_language_in(query, &Lexicon::english())
+}
+
+/// Like `interpret_natural_language`, for a query in `lexicon`'s language.
+/// Clause spans and ignored words refer to the query as written.
+pub fn interpret_natural_language_in(query: &str, lexicon: &Lexicon) -> Result<InterpretedQuery, AppError> {
+    let chars: Vec<char> = query.chars().collect();
+    let tokens = lexicon.translate(tokenizer::tokenize(query));
+    let primary = read(&chars, &tokens, Choices::default())?;
+
+    let mut seen = vec![filters_key(&primary.filters)];
+    let mut alternatives = Vec::new();
+    for choices in Choices::default().alternatives(primary.consulted) {
+        // A reading that conflicts with itself is no alternative.
+        let Ok(reading) = read(&chars, &tokens, choices) else {
+            continue;
+        };
+        let key = filters_key(&reading.filters);
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        alternatives.push(Interpretation {
+            parsed_filters: reading.filters,
+            confidence: reading.confidence,
+            clauses: reading.clauses,
+        });
+    }
+    alternatives.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    Ok(InterpretedQuery {
+        original: query.to_string(),
+        language: lexicon.locale().to_string(),
+        parsed_filters: primary.filters,
+        confidence: primary.confidence,
+        clauses: primary.clauses,
+        ignored_words: primary.ignored_words,
+        sort_by: primary.order.sort.map(|(key, _)| key),
+        order: primary.order.sort.map(|(_, order)| order),
+        limit: primary.order.limit,
+        alternatives,
+    })
+}
+
+struct Reading {
+    filters: Filters,
+    confidence: f64,
+    clauses: Vec<QueryClause>,
+    ignored_words: Vec<QueryWord>,
+    order: ResultOrder,
+    consulted: Choices,
+}
+
+/// Parses and compiles the query with the ambiguous points settled by `choices`.
+///
+/// Confidence is the product of the clause confidences, scaled by how sure the
+/// grouping is and by the share of non-filler words that were used.
+fn read(chars: &[char], tokens: &[Token], choices: Choices) -> Result<Reading, AppError> {
+    let mut parser = Parser::new(chars, tokens, choices);
+    let node = parser.parse();
+    let order = parser
+        .orderings
+        .iter()
+        .try_fold(ResultOrder::default(), |order, (next, _)| merge_order(order, *next))?;
+    // Sorting or limiting alone is a query over every string.
+    let ordered = !parser.orderings.is_empty();
+    let filters = match node {
+        Some(node) => Compiler { constrained: ordered, ..Compiler::default() }.compile(node)?,
+        None if ordered => Filters::default(),
+        None => return Err(unrecognized()),
+    };
+
+    let text = |start: usize, end: usize| chars[start..end].iter().collect::<String>();
+    let clause = |range: &std::ops::Range<usize>, filter: String, confidence: f64| {
+        let start = tokens[range.start].start;
+        let end = tokens[range.end - 1].end;
+        QueryClause {
+            text: text(start, end),
+            start,
+            end,
+            filter,
+            confidence: round(confidence),
+        }
+    };
+    let mut clauses: Vec<QueryClause> = parser
+        .clauses
+        .iter()
+        .map(|c| clause(&c.tokens, describe(&c.node), c.confidence))
+        .chain(parser.orderings.iter().map(|(order, range)| clause(range, describe_order(order), 1.0)))
+        .collect();
+    clauses.sort_by_key(|c| c.start);
+    let ignored_words = parser
+        .ignored
+        .iter()
+        .map(|&i| QueryWord {
+            word: text(tokens[i].start, tokens[i].end),
+            start: tokens[i].start,
+            end: tokens[i].end,
+        })
+        .collect();
+
+    let used: usize = parser.clauses.iter().map(|c| c.tokens.len()).sum::<usize>()
+        + parser.orderings.iter().map(|(_, range)| range.len()).sum::<usize>();
+    let coverage = used as f64 / (used + parser.ignored.len()) as f64;
+    let confidence = parser.clauses.iter().map(|c| c.confidence).product::<f64>()
+        * parser.structure_confidence
+        * coverage;
+
+    Ok(Reading {
+        filters,
+        confidence: round(confidence),
+        clauses,
+        ignored_words,
+        order,
+        consulted: parser.consulted,
+    })
+}
+
+/// Two sorting or limiting phrases agree only if they ask for the same thing.
+fn merge_order(current: ResultOrder, next: ResultOrder) -> Result<ResultOrder, AppError> {
+    if let Some(limit) = next.limit {
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(AppError::invalid_param(
+                "query",
+                format!("limit must be between 1 and {}", MAX_LIMIT),
+            ));
+        }
+    }
+    let sort = match (current.sort, next.sort) {
+        (Some(a), Some(b)) if a != b => return Err(conflict("results sorted two ways")),
+        (a, b) => a.or(b),
+    };
+    let limit = match (current.limit, next.limit) {
+        (Some(a), Some(b)) if a != b => return Err(conflict("two different limits")),
+        (a, b) => a.or(b),
+    };
+    Ok(ResultOrder { sort, limit })
+}
+
+/// A sorting or limiting clause as the listing parameters that do the same.
+fn describe_order(order: &ResultOrder) -> String {
+    let mut params = Vec::new();
+    if let Some((key, direction)) = order.sort {
+        params.push(format!("sort_by={}", key.name()));
+        params.push(format!("order={}", direction.name()));
+    }
+    if let Some(limit) = order.limit {
+        params.push(format!("limit={}", limit));
+    }
+    params.join("&")
+}
+
+fn round(confidence: f64) -> f64 {
+    (confidence * 100.0).round() / 100.0
+}
+
+fn filters_key(filters: &Filters) -> serde_json::Value {
+    serde_json::to_value(filters).unwrap_or_default()
+}
+
+/// A clause in `filter=` syntax. "starts with" and "ends with", which that
+/// syntax lacks, are written in the same style.
+fn describe(node: &Node) -> String {
+    match node {
+        Node::Condition(condition) => describe_condition(condition),
+        Node::Not(inner) => match &**inner {
+            Node::Condition(condition) => match condition.negate() {
+                Some(negated) => describe_condition(&negated),
+                None => format!("NOT {}", describe_condition(condition)),
+            },
+            Node::Not(node) => describe(node),
+            node => format!("NOT {}", describe(node)),
+        },
+        Node::And(nodes) => format!("({})", nodes.iter().map(describe).collect::<Vec<_>>().join(" AND ")),
+        Node::Or(nodes) => format!("({})", nodes.iter().map(describe).collect::<Vec<_>>().join(" OR ")),
+    }
+}
+
+fn describe_condition(condition: &Condition) -> String {
+    match condition {
+        Condition::StartsWith(prefix) => format!("value STARTS WITH {}", Literal::Str(prefix.clone())),
+        Condition::EndsWith(suffix) => format!("value ENDS WITH {}", Literal::Str(suffix.clone())),
+        Condition::CaseInsensitive => "case_insensitive = true".to_string(),
+        condition => condition_expression(condition)
+            .map(|expr| expr.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// The condition as a `filter` expression, for those the syntax can state.
+fn condition_expression(condition: &Condition) -> Option<FilterExpr> {
+    let expr = match condition {
+        Condition::Palindrome(wanted) => compare(Field::IsPalindrome, CmpOp::Eq, Literal::Bool(*wanted)),
+        Condition::Range(measure, bounds) => range_expression(*measure, *bounds),
+        Condition::ContainsChars(letters, mode) => {
+            let checks = letters.iter().map(|c| contains(c.to_string()));
+            match mode {
+                ContainsMode::All => checks.reduce(and),
+                ContainsMode::Any => checks.reduce(or),
+            }?
+        }
+        Condition::Contains(text) => contains(text.clone()),
+        Condition::StartsWithClass(class) => FilterExpr::Is { field: Field::FirstCharacter, class: *class },
+        Condition::EndsWithClass(class) => FilterExpr::Is { field: Field::LastCharacter, class: *class },
+        Condition::Only(class) => only(*class)
+            .iter()
+            .filter_map(condition_expression)
+            .reduce(and)?,
+        Condition::StartsWith(_) | Condition::EndsWith(_) | Condition::CaseInsensitive => return None,
+    };
+    Some(expr)
+}
+
+/// "only consonants" as ranges: at least one consonant, and no vowels or digits.
+fn only(class: CharClass) -> Vec<Condition> {
+    let mut conditions = vec![Condition::Range(Measure::Count(class), Bounds { min: Some(1), max: None })];
+    conditions.extend(
+        [CharClass::Vowel, CharClass::Consonant, CharClass::Digit]
+            .into_iter()
+            .filter(|&other| other != class)
+            .map(|other| Condition::Range(Measure::Count(other), Bounds { min: None, max: Some(0) })),
+    );
+    conditions
+}
+
+fn unrecognized() -> AppError {
+    AppError::UnrecognizedQuery("Unable to parse natural language query".to_string())
+}
+
+/// Folds a condition tree into `Filters`. Top-level conjuncts set the flat
+/// fields; everything else is AND-ed into the `filter` expression.
+#[derive(Default)]
+struct Compiler {
+    filters: Filters,
+    length: Bounds,
+    word_count: Bounds,
+    unique_characters: Bounds,
+    /// Character class counts, which only the `filter` expression can bound.
+    counts: Vec<(CharClass, Bounds)>,
+    /// Letters every match must contain.
+    letters: Vec<char>,
+    any_letters: Option<Vec<char>>,
+    expressions: Vec<FilterExpr>,
+    /// Whether anything beyond a case modifier was recognized.
+    constrained: bool,
+}
+
+impl Compiler {
+    fn compile(mut self, node: Node) -> Result<Filters, AppError> {
+        self.add(node)?;
+        if !self.constrained {
+            return Err(unrecognized());
+        }
+        self.finish()
+    }
+
+    fn add(&mut self, node: Node) -> Result<(), AppError> {
+        match node {
+            Node::And(nodes) => nodes.into_iter().try_for_each(|n| self.add(n)),
+            Node::Condition(condition) => self.add_condition(condition),
+            Node::Not(inner) => match *inner {
+                Node::Not(node) => self.add(*node),
+                Node::Condition(condition) => match condition.negate() {
+                    Some(negated) => self.add_condition(negated),
+                    None => self.add_expression(Node::Not(Box::new(Node::Condition(condition)))),
+                },
+                node => self.add_expression(Node::Not(Box::new(node))),
+            },
+            node @ Node::Or(_) => self.add_expression(node),
+        }
+    }
+
+    fn add_condition(&mut self, condition: Condition) -> Result<(), AppError> {
+        self.constrained |= condition != Condition::CaseInsensitive;
+        match condition {
+            Condition::Palindrome(wanted) => {
+                if self.filters.is_palindrome == Some(!wanted) {
+                    return Err(conflict("palindromes and non-palindromes"));
+                }
+                self.filters.is_palindrome = Some(wanted);
+            }
+            Condition::Range(measure, bounds) => {
+                let current = match measure {
+                    Measure::Length => &mut self.length,
+                    Measure::WordCount => &mut self.word_count,
+                    Measure::UniqueCharacters => &mut self.unique_characters,
+                    Measure::Count(class) => self.count_bounds(class),
+                };
+                *current = current.intersect(bounds);
+            }
+            Condition::Only(class) => {
+                for condition in only(class) {
+                    self.add_condition(condition)?;
+                }
+            }
+            condition @ (Condition::StartsWithClass(_) | Condition::EndsWithClass(_)) => {
+                self.add_expression(Node::Condition(condition))?;
+            }
+            // Only one any-of list fits in the flat fields.
+            Condition::ContainsChars(letters, ContainsMode::Any) if letters.len() > 1 => {
+                if self.any_letters.is_none() {
+                    self.any_letters = Some(letters);
+                } else {
+                    self.add_expression(Node::Condition(Condition::ContainsChars(letters, ContainsMode::Any)))?;
+                }
+            }
+            Condition::ContainsChars(letters, _) => {
+                for letter in letters {
+                    if !self.letters.contains(&letter) {
+                        self.letters.push(letter);
+                    }
+                }
+            }
+            Condition::Contains(text) if self.filters.contains.is_none() => self.filters.contains = Some(text),
+            Condition::Contains(text) => self.add_expression(Node::Condition(Condition::Contains(text)))?,
+            Condition::StartsWith(prefix) => {
+                let current = self.filters.starts_with.take();
+                self.filters.starts_with = Some(merge_affix(current, prefix, "starts with", |a, b| a.starts_with(b))?);
+            }
+            Condition::EndsWith(suffix) => {
+                let current = self.filters.ends_with.take();
+                self.filters.ends_with = Some(merge_affix(current, suffix, "ends with", |a, b| a.ends_with(b))?);
+            }
+            Condition::CaseInsensitive => self.filters.case_insensitive = Some(true),
+        }
+        Ok(())
+    }
+
+    fn count_bounds(&mut self, class: CharClass) -> &mut Bounds {
+        let index = match self.counts.iter().position(|(c, _)| *c == class) {
+            Some(index) => index,
+            None => {
+                self.counts.push((class, Bounds::default()));
+                self.counts.len() - 1
+            }
+        };
+        &mut self.counts[index].1
+    }
+
+    fn add_expression(&mut self, node: Node) -> Result<(), AppError> {
+        if let Some(expr) = self.expression_for(node)? {
+            self.constrained = true;
+            self.expressions.push(expr);
+        }
+        Ok(())
+    }
+
+    /// `None` for branches that only carry a case modifier.
+    fn expression_for(&mut self, node: Node) -> Result<Option<FilterExpr>, AppError> {
+        let expr = match node {
+            Node::Condition(Condition::CaseInsensitive) => {
+                self.filters.case_insensitive = Some(true);
+                return Ok(None);
+            }
+            Node::Condition(condition) => condition_expression(&condition).ok_or_else(|| {
+                AppError::UnrecognizedQuery(
+                    "\"starts with\" and \"ends with\" cannot be negated or combined with \"or\"".to_string(),
+                )
+            })?,
+            Node::Not(inner) => match self.expression_for(*inner)? {
+                Some(expr) => FilterExpr::Not(Box::new(expr)),
+                None => return Ok(None),
+            },
+            Node::And(nodes) => match self.expressions_for(nodes)?.into_iter().reduce(and) {
+                Some(expr) => expr,
+                None => return Ok(None),
+            },
+            Node::Or(nodes) => match self.expressions_for(nodes)?.into_iter().reduce(or) {
+                Some(expr) => expr,
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(expr))
+    }
+
+    fn expressions_for(&mut self, nodes: Vec<Node>) -> Result<Vec<FilterExpr>, AppError> {
+        let mut exprs = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            exprs.extend(self.expression_for(node)?);
+        }
+        Ok(exprs)
+    }
+
+    fn finish(mut self) -> Result<Filters, AppError> {
+        for (name, bounds) in [
+            ("min_length > max_length", self.length),
+            ("min_word_count > max_word_count", self.word_count),
+            ("min_unique_characters > max_unique_characters", self.unique_characters),
+        ] {
+            if bounds.is_empty() {
+                return Err(conflict(name));
+            }
+        }
+        for (class, bounds) in self.counts {
+            if bounds.is_empty() {
+                return Err(conflict(&format!("no {}_count satisfies every clause", class.name())));
+            }
+            self.expressions.push(range_expression(Measure::Count(class), bounds));
+        }
+
+        let filters = &mut self.filters;
+        filters.min_length = self.length.min;
+        filters.max_length = self.length.max;
+        match (self.word_count.min, self.word_count.max) {
+            (Some(min), Some(max)) if min == max => filters.word_count = Some(min),
+            (min, max) => {
+                filters.min_word_count = min;
+                filters.max_word_count = max;
+            }
+        }
+        filters.min_unique_characters = self.unique_characters.min;
+        filters.max_unique_characters = self.unique_characters.max;
+
+        // A single required letter goes in `contains_character`, which leaves
+        // `contains_characters` free for an any-of list.
+        if let [letter] = self.letters[..] {
+            filters.contains_character = Some(letter.to_string());
+            self.letters.clear();
+        }
+        let graphemes = |letters: Vec<char>| letters.into_iter().map(String::from).collect();
+        match (self.letters.is_empty(), self.any_letters) {
+            (true, None) => {}
+            (true, Some(any)) => {
+                filters.contains_characters = Some(graphemes(any));
+                filters.contains_mode = Some(ContainsMode::Any);
+            }
+            (false, None) => {
+                filters.contains_characters = Some(graphemes(self.letters));
+                filters.contains_mode = Some(ContainsMode::All);
+            }
+            (false, Some(any)) => {
+                filters.contains_characters = Some(graphemes(any));
+                filters.contains_mode = Some(ContainsMode::Any);
+                self.expressions.extend(self.letters.into_iter().map(|c| contains(c.to_string())));
+            }
+        }
+
+        filters.filter = self.expressions.into_iter().reduce(and);
+        Ok(self.filters)
+    }
+}
+
+fn conflict(what: &str) -> AppError {
+    AppError::ConflictingFilters(format!("Conflicting filters: {}", what))
+}
+
+/// Two "starts with" clauses agree only if one extends the other; the longer wins.
+fn merge_affix(
+    current: Option<String>,
+    new: String,
+    name: &str,
+    extends: impl Fn(&str, &str) -> bool,
+) -> Result<String, AppError> {
+    match current {
+        None => Ok(new),
+        Some(current) if extends(&current, &new) => Ok(current),
+        Some(current) if extends(&new, &current) => Ok(new),
+        Some(_) => Err(conflict(name)),
+    }
+}
+
+fn compare(field: Field, op: CmpOp, value: Literal) -> FilterExpr {
+    FilterExpr::Compare { field, op, value }
+}
+
+fn contains(value: String) -> FilterExpr {
+    FilterExpr::Contains { field: Field::Value, value }
+}
+
+fn and(a: FilterExpr, b: FilterExpr) -> FilterExpr {
+    FilterExpr::And(Box::new(a), Box::new(b))
+}
+
+fn or(a: FilterExpr, b: FilterExpr) -> FilterExpr {
+    FilterExpr::Or(Box::new(a), Box::new(b))
+}
+
+fn range_expression(measure: Measure, bounds: Bounds) -> FilterExpr {
+    let field = match measure {
+        Measure::Length => Field::Length,
+        Measure::WordCount => Field::WordCount,
+        Measure::UniqueCharacters => Field::UniqueCharacters,
+        Measure::Count(class) => Field::count_of(class),
+    };
+    let int = |n: usize| Literal::Int(i64::try_from(n).unwrap_or(i64::MAX));
+    match (bounds.min, bounds.max) {
+        (Some(min), Some(max)) if min == max => compare(field, CmpOp::Eq, int(min)),
+        (Some(min), Some(max)) => and(compare(field, CmpOp::Ge, int(min)), compare(field, CmpOp::Le, int(max))),
+        (Some(min), None) => compare(field, CmpOp::Ge, int(min)),
+        (None, Some(0)) => compare(field, CmpOp::Eq, int(0)),
+        (None, Some(max)) => compare(field, CmpOp::Le, int(max)),
+        // An unbounded range matches everything.
+        (None, None) => compare(field, CmpOp::Ge, int(0)),
+    }
+}
@@ -0,0 +1,111 @@
+/// Words are lowercased; quoted text keeps its case so it can be matched
+/// literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    Word(String),
+    Number(usize),
+    Quoted(String),
+    Comma,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Character offsets into the query, end exclusive.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn word(&self) -> Option<&str> {
+        match &self.kind {
+            TokenKind::Word(w) => Some(w),
+            _ => None,
+        }
+    }
+
+    /// A word of exactly one letter, as in "containing the letter x".
+    pub fn letter(&self) -> Option<char> {
+        let mut chars = self.word()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_alphabetic() => Some(c),
+            _ => None,
+        }
+    }
+}
+
+fn closing_quote(open: char) -> Option<char> {
+    match open {
+        '"' => Some('"'),
+        '\'' => Some('\''),
+        '“' => Some('”'),
+        '‘' => Some('’'),
+        _ => None,
+    }
+}
+
+/// Splits a query into words, numbers, quoted strings and commas. Other
+/// punctuation, including hyphens, only separates words, so "case-insensitive"
+/// reads as "case insensitive".
+pub fn tokenize(query: &str) -> Vec<Token> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        if c == ',' || c == ';' {
+            i += 1;
+            tokens.push(Token { kind: TokenKind::Comma, start, end: i });
+            continue;
+        }
+
+        // A quote only opens at the start of a word, so apostrophes in
+        // "doesn't" are left alone.
+        let at_word_start = i == 0 || !chars[i - 1].is_alphanumeric();
+        if let Some(close) = closing_quote(c).filter(|_| at_word_start) {
+            if let Some(len) = chars[i + 1..].iter().position(|&ch| ch == close) {
+                let text: String = chars[i + 1..i + 1 + len].iter().collect();
+                i += len + 2;
+                if !text.is_empty() {
+                    tokens.push(Token { kind: TokenKind::Quoted(text), start, end: i });
+                }
+                continue;
+            }
+        }
+
+        if c.is_ascii_digit() {
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let kind = text.parse().map(TokenKind::Number).unwrap_or(TokenKind::Word(text));
+            tokens.push(Token { kind, start, end: i });
+            continue;
+        }
+
+        if c.is_alphabetic() {
+            let mut word = String::new();
+            while i < chars.len() {
+                let ch = chars[i];
+                let apostrophe = matches!(ch, '\'' | '’') && chars.get(i + 1).is_some_and(|n| n.is_alphabetic());
+                if ch.is_alphabetic() {
+                    word.extend(ch.to_lowercase());
+                } else if apostrophe {
+                    word.push('\'');
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+            tokens.push(Token { kind: TokenKind::Word(word), start, end: i });
+            continue;
+        }
+
+        i += 1;
+    }
+
+    tokens
+}
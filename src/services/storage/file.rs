@@ -0,0 +1,301 @@
+use crate::error::AppError;
+use crate::models::string::{StoredString, Filters};
+use super::indexes::IndexedStrings;
+use crate::utils::pagination::{finish_page, Cursor, Pagination};
+use super::{reanalyze, StringStore};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const JOURNAL_FILE: &str = "journal.ndjson";
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const SNAPSHOT_TMP_FILE: &str = "snapshot.json.tmp";
+
+/// Version of the snapshot and journal records. Anything older is
+/// re-analyzed on load, since earlier releases measured `length` in bytes and
+/// did not store the code point, grapheme and character class counts.
+const FORMAT_VERSION: u32 = 1;
+
+/// A single entry in the append-only journal. Each record is written as one
+/// JSON line; a record only counts once its trailing newline is on disk.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalRecord {
+    Save { string: Box<StoredString> },
+    /// A batch written as one line, so a torn write drops all of it.
+    SaveAll { strings: Vec<StoredString> },
+    Delete { id: String },
+}
+
+/// A journal line: the record plus the format it was written in. Records from
+/// before versioning have no `version` and read as 0.
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalLine {
+    #[serde(default)]
+    version: u32,
+    #[serde(flatten)]
+    record: JournalRecord,
+}
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    version: u32,
+    strings: Vec<&'a StoredString>,
+}
+
+/// Snapshots used to be a bare array of strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SnapshotFile {
+    Versioned { version: u32, strings: Vec<StoredString> },
+    Legacy(Vec<StoredString>),
+}
+
+struct FileState {
+    strings: IndexedStrings,
+    journal: File,
+    records_since_snapshot: usize,
+}
+
+/// Durable store that keeps the working set in memory, appends every mutation
+/// to a journal before applying it, and periodically compacts the journal
+/// into a snapshot.
+pub struct FileStore {
+    dir: PathBuf,
+    snapshot_interval: usize,
+    state: Mutex<FileState>,
+}
+
+impl FileStore {
+    pub fn open(dir: impl AsRef<Path>, snapshot_interval: usize) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut strings = load_snapshot(&dir.join(SNAPSHOT_FILE))?;
+        let records_since_snapshot = replay_journal(&dir.join(JOURNAL_FILE), &mut strings)?;
+
+        let journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(JOURNAL_FILE))?;
+
+        log::info!(
+            "Loaded {} strings from {} ({} journal records)",
+            strings.len(),
+            dir.display(),
+            records_since_snapshot
+        );
+
+        Ok(FileStore {
+            dir,
+            snapshot_interval,
+            state: Mutex::new(FileState {
+                strings,
+                journal,
+                records_since_snapshot,
+            }),
+        })
+    }
+
+    /// Writes the current contents to a fresh snapshot and truncates the journal.
+    pub fn compact(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        self.compact_locked(&mut state)
+    }
+
+    fn compact_locked(&self, state: &mut FileState) -> io::Result<()> {
+        let tmp_path = self.dir.join(SNAPSHOT_TMP_FILE);
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            let snapshot = Snapshot {
+                version: FORMAT_VERSION,
+                strings: state.strings.values().collect(),
+            };
+            serde_json::to_writer(&mut writer, &snapshot)?;
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        fs::rename(&tmp_path, self.dir.join(SNAPSHOT_FILE))?;
+
+        state.journal.set_len(0)?;
+        state.journal.sync_all()?;
+        state.records_since_snapshot = 0;
+        Ok(())
+    }
+
+    fn append(&self, state: &mut FileState, record: JournalRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(&JournalLine { version: FORMAT_VERSION, record })?;
+        line.push(b'\n');
+        let len_before = state.journal.metadata()?.len();
+        if let Err(e) = state.journal.write_all(&line).and_then(|_| state.journal.sync_data()) {
+            // Roll back a partial write so later records are not appended to a torn line.
+            let _ = state.journal.set_len(len_before);
+            return Err(e);
+        }
+        state.records_since_snapshot += 1;
+        Ok(())
+    }
+
+    fn maybe_compact(&self, state: &mut FileState) {
+        if self.snapshot_interval == 0 || state.records_since_snapshot < self.snapshot_interval {
+            return;
+        }
+        if let Err(e) = self.compact_locked(state) {
+            log::warn!("Snapshot compaction failed, journal kept: {}", e);
+        }
+    }
+}
+
+impl StringStore for FileStore {
+    fn save_string(&self, string: StoredString) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        if state.strings.contains(&string.id) || state.strings.digest_conflict(&string).is_some() {
+            return Err(AppError::AlreadyExists("String already exists".to_string()));
+        }
+        self.append(&mut state, JournalRecord::Save { string: Box::new(string.clone()) })
+            .map_err(|e| AppError::Storage(format!("Failed to write journal: {}", e)))?;
+        state.strings.insert(string)?;
+        self.maybe_compact(&mut state);
+        Ok(())
+    }
+
+    fn save_all(&self, strings: Vec<StoredString>) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(conflict) = state.strings.find_conflict(&strings) {
+            return Err(AppError::AlreadyExists(format!("String already exists: {}", conflict.value)));
+        }
+        self.append(&mut state, JournalRecord::SaveAll { strings: strings.clone() })
+            .map_err(|e| AppError::Storage(format!("Failed to write journal: {}", e)))?;
+        for string in strings {
+            state.strings.insert(string)?;
+        }
+        self.maybe_compact(&mut state);
+        Ok(())
+    }
+
+    fn get_string_by_hash(&self, hash: &str) -> Result<Option<StoredString>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.strings.get(hash).cloned())
+    }
+
+    fn get_string_by_digest(&self, digest: &str) -> Result<Option<StoredString>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.strings.get_by_digest(&digest.to_lowercase()).cloned())
+    }
+
+    fn get_all_strings(&self, filters: &Filters) -> Result<Vec<StoredString>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.strings.filter(filters))
+    }
+
+    fn get_page(&self, filters: &Filters, pagination: &Pagination) -> Result<(Vec<StoredString>, Option<Cursor>), AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(finish_page(state.strings.page(filters, pagination, pagination.fetch_limit()), pagination))
+    }
+
+    fn delete_string(&self, value: &str) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        let id = match state.strings.get_by_value(value) {
+            Some(found) => found.id.clone(),
+            None => return Err(AppError::NotFound("String not found".to_string())),
+        };
+        self.append(&mut state, JournalRecord::Delete { id: id.clone() })
+            .map_err(|e| AppError::Storage(format!("Failed to write journal: {}", e)))?;
+        state.strings.remove(&id);
+        self.maybe_compact(&mut state);
+        Ok(())
+    }
+
+    fn string_exists(&self, hash: &str) -> Result<bool, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.strings.contains(hash))
+    }
+}
+
+fn load_snapshot(path: &Path) -> io::Result<IndexedStrings> {
+    let mut indexed = IndexedStrings::new();
+    if !path.exists() {
+        return Ok(indexed);
+    }
+    let file = File::open(path)?;
+    let (version, strings) = match serde_json::from_reader(io::BufReader::new(file))? {
+        SnapshotFile::Versioned { version, strings } => (version, strings),
+        SnapshotFile::Legacy(strings) => (0, strings),
+    };
+    if version < FORMAT_VERSION {
+        log::info!("Re-analyzing {} strings from a version {} snapshot", strings.len(), version);
+    }
+    for string in strings {
+        restore(&mut indexed, string, version);
+    }
+    Ok(indexed)
+}
+
+/// Loads a string written in format `version`, re-analyzing it if that is
+/// older than the current one. Writes check for digest conflicts, so one here
+/// predates that check; the later string is dropped.
+fn restore(strings: &mut IndexedStrings, string: StoredString, version: u32) {
+    let string = if version < FORMAT_VERSION { reanalyze(string) } else { string };
+    let value = string.value.clone();
+    if let Err(e) = strings.insert(string) {
+        log::warn!("Skipping stored string {:?}: {}", value, e);
+    }
+}
+
+/// Replays the journal into `strings` and returns the number of records applied.
+/// A torn final record (missing newline or unparseable) is dropped and the
+/// journal truncated back to the last complete record; corruption anywhere
+/// else is reported as an error.
+fn replay_journal(path: &Path, strings: &mut IndexedStrings) -> io::Result<usize> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let mut applied = 0;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let Some(end) = bytes[offset..].iter().position(|&b| b == b'\n').map(|i| offset + i) else {
+            break;
+        };
+        match serde_json::from_slice::<JournalLine>(&bytes[offset..end]) {
+            Ok(JournalLine { version, record: JournalRecord::Save { string } }) => {
+                restore(strings, *string, version);
+            }
+            Ok(JournalLine { version, record: JournalRecord::SaveAll { strings: batch } }) => {
+                for string in batch {
+                    restore(strings, string, version);
+                }
+            }
+            Ok(JournalLine { record: JournalRecord::Delete { id }, .. }) => {
+                strings.remove(&id);
+            }
+            Err(e) if end + 1 == bytes.len() => {
+                log::warn!("Discarding torn journal record at byte {}: {}", offset, e);
+                break;
+            }
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Corrupt journal record at byte {}: {}", offset, e),
+                ));
+            }
+        }
+        applied += 1;
+        offset = end + 1;
+    }
+
+    if offset < bytes.len() {
+        log::warn!(
+            "Truncating {} trailing bytes from {}",
+            bytes.len() - offset,
+            path.display()
+        );
+        OpenOptions::new().write(true).open(path)?.set_len(offset as u64)?;
+    }
+
+    Ok(applied)
+}
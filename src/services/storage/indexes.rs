@@ -0,0 +1,305 @@
+use crate::error::AppError;
+use crate::models::string::{StoredString, Filters, ContainsMode, SortKey, SortOrder};
+use crate::services::hashing::{compute_sha256, HashAlgorithm};
+use crate::utils::pagination::{sort_value, Pagination};
+use super::apply_filters;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::Bound;
+
+type IdSet = HashSet<String>;
+
+/// A digest together with the algorithm that produced it.
+type DigestKey = (HashAlgorithm, String);
+
+/// In-memory map of strings plus secondary indexes that are kept in sync on
+/// every insert and remove, so filtered listings only visit candidate ids.
+#[derive(Default)]
+pub struct IndexedStrings {
+    strings: HashMap<String, StoredString>,
+    by_length: BTreeMap<usize, IdSet>,
+    by_word_count: HashMap<usize, IdSet>,
+    by_palindrome: HashMap<bool, IdSet>,
+    by_character: HashMap<char, IdSet>,
+    /// Every stored digest (SHA-256 plus `hashes`) mapped to the owning id.
+    /// Equal digests from different algorithms are separate keys.
+    by_digest: HashMap<DigestKey, String>,
+    /// Ids in listing order for every sort key, so a page is a range scan.
+    by_sort_key: HashMap<SortKey, BTreeSet<(i64, String)>>,
+}
+
+impl IndexedStrings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.strings.contains_key(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&StoredString> {
+        self.strings.get(id)
+    }
+
+    /// Resolves an id or a stored digest of any algorithm to the string it
+    /// belongs to. Algorithms are tried in `HashAlgorithm::ALL` order.
+    pub fn get_by_digest(&self, digest: &str) -> Option<&StoredString> {
+        self.strings.get(digest).or_else(|| {
+            HashAlgorithm::ALL
+                .iter()
+                .find_map(|algorithm| self.by_digest.get(&(*algorithm, digest.to_string())))
+                .and_then(|id| self.strings.get(id))
+        })
+    }
+
+    /// Resolves a value through its SHA-256 digest, which every string has.
+    pub fn get_by_value(&self, value: &str) -> Option<&StoredString> {
+        self.by_digest
+            .get(&(HashAlgorithm::Sha256, compute_sha256(value)))
+            .and_then(|id| self.strings.get(id))
+            .filter(|found| found.value == value)
+    }
+
+    /// Returns a digest of `string` that is already mapped to another id
+    /// under the same algorithm.
+    pub fn digest_conflict<'a>(&self, string: &'a StoredString) -> Option<&'a str> {
+        string
+            .digests()
+            .find(|(algorithm, digest)| {
+                self.by_digest
+                    .get(&(*algorithm, digest.to_string()))
+                    .is_some_and(|id| *id != string.id)
+            })
+            .map(|(_, digest)| digest)
+    }
+
+    /// Returns the first string in `strings` that is already stored, directly
+    /// or under another id, or that repeats an earlier entry's id or digest.
+    pub fn find_conflict<'a>(&self, strings: &'a [StoredString]) -> Option<&'a StoredString> {
+        let mut seen = HashSet::new();
+        let mut seen_digests: HashMap<(HashAlgorithm, &str), &str> = HashMap::new();
+        strings.iter().find(|s| {
+            self.contains(&s.id)
+                || self.digest_conflict(s).is_some()
+                || !seen.insert(s.id.as_str())
+                || s.digests().any(|key| *seen_digests.entry(key).or_insert(&s.id) != s.id)
+        })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &StoredString> {
+        self.strings.values()
+    }
+
+    /// Inserts `string`, replacing and unindexing any previous entry with the
+    /// same id. Fails if one of its digests already belongs to another string.
+    pub fn insert(&mut self, string: StoredString) -> Result<(), AppError> {
+        if let Some(digest) = self.digest_conflict(&string) {
+            return Err(AppError::AlreadyExists(format!("Digest {} belongs to another string", digest)));
+        }
+        self.remove(&string.id);
+
+        let id = &string.id;
+        let props = &string.properties;
+        self.by_length.entry(props.length).or_default().insert(id.clone());
+        self.by_word_count.entry(props.word_count).or_default().insert(id.clone());
+        self.by_palindrome.entry(props.is_palindrome).or_default().insert(id.clone());
+        for ch in string.value.chars() {
+            self.by_character.entry(ch).or_default().insert(id.clone());
+        }
+        for (algorithm, digest) in string.digests() {
+            self.by_digest.insert((algorithm, digest.to_string()), id.clone());
+        }
+        for key in SortKey::ALL {
+            self.by_sort_key.entry(key).or_default().insert((sort_value(&string, key), id.clone()));
+        }
+
+        self.strings.insert(string.id.clone(), string);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<StoredString> {
+        let string = self.strings.remove(id)?;
+        let props = &string.properties;
+        if let Some(ids) = self.by_length.get_mut(&props.length) {
+            ids.remove(id);
+            if ids.is_empty() {
+                self.by_length.remove(&props.length);
+            }
+        }
+        remove_from_bucket(&mut self.by_word_count, &props.word_count, id);
+        remove_from_bucket(&mut self.by_palindrome, &props.is_palindrome, id);
+        for ch in string.value.chars() {
+            remove_from_bucket(&mut self.by_character, &ch, id);
+        }
+        // Only unmap digests this string owns; insert never lets two strings
+        // share one.
+        for (algorithm, digest) in string.digests() {
+            let key = (algorithm, digest.to_string());
+            if self.by_digest.get(&key).map(String::as_str) == Some(id) {
+                self.by_digest.remove(&key);
+            }
+        }
+        for key in SortKey::ALL {
+            if let Some(ordered) = self.by_sort_key.get_mut(&key) {
+                ordered.remove(&(sort_value(&string, key), string.id.clone()));
+            }
+        }
+        Some(string)
+    }
+
+    pub fn filter(&self, filters: &Filters) -> Vec<StoredString> {
+        match self.candidates(filters) {
+            Some(ids) => ids
+                .into_iter()
+                .filter_map(|id| self.strings.get(&id))
+                .filter(|s| apply_filters(s, filters))
+                .cloned()
+                .collect(),
+            None => self.strings
+                .values()
+                .filter(|s| apply_filters(s, filters))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the strings matching `filters` in `pagination` order, starting
+    /// after its cursor, and at most `limit` of them.
+    pub fn page(&self, filters: &Filters, pagination: &Pagination, limit: Option<usize>) -> Vec<StoredString> {
+        let limit = limit.unwrap_or(usize::MAX);
+        if let Some(ids) = self.candidates(filters) {
+            // Few enough to sort directly, rather than walking the whole order.
+            let mut matched: Vec<&StoredString> = ids
+                .iter()
+                .filter_map(|id| self.strings.get(id))
+                .filter(|s| pagination.is_after_cursor(s) && apply_filters(s, filters))
+                .collect();
+            matched.sort_by_cached_key(|s| (sort_value(s, pagination.sort_by), s.id.as_str()));
+            if pagination.order == SortOrder::Desc {
+                matched.reverse();
+            }
+            return matched.into_iter().take(limit).cloned().collect();
+        }
+
+        let Some(ordered) = self.by_sort_key.get(&pagination.sort_by) else {
+            return Vec::new();
+        };
+        let after = pagination.after().map(|(key, id)| (key, id.to_string()));
+        let ids: Box<dyn Iterator<Item = &(i64, String)>> = match (pagination.order, after) {
+            (SortOrder::Asc, Some(after)) => Box::new(ordered.range((Bound::Excluded(after), Bound::Unbounded))),
+            (SortOrder::Asc, None) => Box::new(ordered.iter()),
+            (SortOrder::Desc, Some(after)) => Box::new(ordered.range(..after).rev()),
+            (SortOrder::Desc, None) => Box::new(ordered.iter().rev()),
+        };
+        ids.filter_map(|(_, id)| self.strings.get(id))
+            .filter(|s| apply_filters(s, filters))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Intersects the index buckets matching `filters`. Returns `None` when no
+    /// indexed filter is set and the caller has to scan everything.
+    fn candidates(&self, filters: &Filters) -> Option<IdSet> {
+        let empty = IdSet::new();
+        let mut sets: Vec<&IdSet> = Vec::new();
+
+        if let Some(is_pal) = filters.is_palindrome {
+            sets.push(self.by_palindrome.get(&is_pal).unwrap_or(&empty));
+        }
+        if let Some(wc) = filters.word_count {
+            sets.push(self.by_word_count.get(&wc).unwrap_or(&empty));
+        }
+        // The character index is case-sensitive, so case-insensitive lookups
+        // fall back to the residual check.
+        let case_sensitive = filters.case_insensitive != Some(true);
+        if case_sensitive {
+            // A grapheme of several code points narrows to strings holding
+            // all of them; the residual check confirms they are adjacent.
+            if let Some(grapheme) = &filters.contains_character {
+                for ch in grapheme.chars() {
+                    sets.push(self.by_character.get(&ch).unwrap_or(&empty));
+                }
+            }
+        }
+        let mut any_of = None;
+        if let (true, Some(graphemes)) = (case_sensitive, &filters.contains_characters) {
+            match filters.contains_mode.unwrap_or_default() {
+                ContainsMode::All => {
+                    for ch in graphemes.iter().flat_map(|grapheme| grapheme.chars()) {
+                        sets.push(self.by_character.get(&ch).unwrap_or(&empty));
+                    }
+                }
+                ContainsMode::Any => {
+                    any_of = Some(
+                        graphemes
+                            .iter()
+                            .flat_map(|grapheme| self.holding_all_of(grapheme))
+                            .collect::<IdSet>(),
+                    );
+                }
+            }
+        }
+        if let Some(ids) = &any_of {
+            sets.push(ids);
+        }
+
+        let length_ids = self.length_candidates(filters);
+        if let Some(ids) = &length_ids {
+            sets.push(ids);
+        }
+
+        sets.sort_by_key(|s| s.len());
+        let (smallest, rest) = sets.split_first()?;
+        Some(
+            smallest
+                .iter()
+                .filter(|id| rest.iter().all(|s| s.contains(*id)))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Ids of the strings holding every code point of `grapheme`.
+    fn holding_all_of(&self, grapheme: &str) -> IdSet {
+        let sets: Option<Vec<&IdSet>> = grapheme.chars().map(|ch| self.by_character.get(&ch)).collect();
+        let Some((first, rest)) = sets.as_deref().and_then(<[&IdSet]>::split_first) else {
+            return IdSet::new();
+        };
+        first.iter().filter(|id| rest.iter().all(|s| s.contains(*id))).cloned().collect()
+    }
+
+    fn length_candidates(&self, filters: &Filters) -> Option<IdSet> {
+        if filters.min_length.is_none() && filters.max_length.is_none() {
+            return None;
+        }
+        let min = filters.min_length.unwrap_or(0);
+        let max = filters.max_length.unwrap_or(usize::MAX);
+        if min > max {
+            return Some(IdSet::new());
+        }
+        Some(
+            self.by_length
+                .range(min..=max)
+                .flat_map(|(_, ids)| ids.iter().cloned())
+                .collect(),
+        )
+    }
+}
+
+fn remove_from_bucket<K: Hash + Eq>(index: &mut HashMap<K, IdSet>, key: &K, id: &str) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::error::AppError;
+use crate::models::string::{StoredString, Filters};
+use super::indexes::IndexedStrings;
+use crate::utils::pagination::{finish_page, Cursor, Pagination};
+use super::StringStore;
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct InMemoryStore {
+    strings: Mutex<IndexedStrings>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StringStore for InMemoryStore {
+    fn save_string(&self, string: StoredString) -> Result<(), AppError> {
+        let mut store = self.strings.lock().unwrap();
+        if store.contains(&string.id) {
+            return Err(AppError::AlreadyExists("String already exists".to_string()));
+        }
+        store.insert(string)
+    }
+
+    fn save_all(&self, strings: Vec<StoredString>) -> Result<(), AppError> {
+        let mut store = self.strings.lock().unwrap();
+        if let Some(conflict) = store.find_conflict(&strings) {
+            return Err(AppError::AlreadyExists(format!("String already exists: {}", conflict.value)));
+        }
+        for string in strings {
+            store.insert(string)?;
+        }
+        Ok(())
+    }
+
+    fn get_string_by_hash(&self, hash: &str) -> Result<Option<StoredString>, AppError> {
+        let store = self.strings.lock().unwrap();
+        Ok(store.get(hash).cloned())
+    }
+
+    fn get_string_by_digest(&self, digest: &str) -> Result<Option<StoredString>, AppError> {
+        let store = self.strings.lock().unwrap();
+        Ok(store.get_by_digest(&digest.to_lowercase()).cloned())
+    }
+
+    fn get_all_strings(&self, filters: &Filters) -> Result<Vec<StoredString>, AppError> {
+        let store = self.strings.lock().unwrap();
+        Ok(store.filter(filters))
+    }
+
+    fn get_page(&self, filters: &Filters, pagination: &Pagination) -> Result<(Vec<StoredString>, Option<Cursor>), AppError> {
+        let store = self.strings.lock().unwrap();
+        Ok(finish_page(store.page(filters, pagination, pagination.fetch_limit()), pagination))
+    }
+
+    fn delete_string(&self, value: &str) -> Result<(), AppError> {
+        let mut store = self.strings.lock().unwrap();
+        let id = store.get_by_value(value).map(|s| s.id.clone());
+        if id.and_then(|id| store.remove(&id)).is_some() {
+            Ok(())
+        } else {
+            Err(AppError::NotFound("String not found".to_string()))
+        }
+    }
+
+    fn string_exists(&self, hash: &str) -> Result<bool, AppError> {
+        let store = self.strings.lock().unwrap();
+        Ok(store.contains(hash))
+    }
+}
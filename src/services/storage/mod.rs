@@ -0,0 +1,257 @@
+pub mod file;
+pub mod indexes;
+pub mod memory;
+pub mod sqlite;
+
+use crate::error::AppError;
+use crate::config::{Config, StorageBackend};
+use crate::models::string::{StoredString, StringProperties, Filters, ContainsMode};
+use actix_web::web;
+use crate::services::analyzer::analyze_string_with_policy;
+use crate::services::hashing::compute_sha256;
+use crate::utils::pagination::{Cursor, Pagination};
+use std::io;
+use std::sync::Arc;
+
+pub use file::FileStore;
+pub use memory::InMemoryStore;
+pub use sqlite::SqliteStore;
+
+pub trait StringStore: Send + Sync {
+    fn save_string(&self, string: StoredString) -> Result<(), AppError>;
+
+    /// Saves every string or none of them. Fails if any id is already stored
+    /// or repeated within `strings`.
+    fn save_all(&self, strings: Vec<StoredString>) -> Result<(), AppError>;
+
+    fn get_string_by_hash(&self, hash: &str) -> Result<Option<StoredString>, AppError>;
+
+    /// Looks a string up by its id or by any digest stored in its properties.
+    fn get_string_by_digest(&self, digest: &str) -> Result<Option<StoredString>, AppError> {
+        let digest = digest.to_lowercase();
+        if let Some(found) = self.get_string_by_hash(&digest)? {
+            return Ok(Some(found));
+        }
+        Ok(self
+            .get_all_strings(&Filters::default())?
+            .into_iter()
+            .find(|s| s.digests().any(|(_, d)| d == digest)))
+    }
+
+    /// The SHA-256 digest is always stored, so this works whichever algorithm
+    /// produced the id. A string found under the digest of another value is
+    /// not returned.
+    fn get_string_by_value(&self, value: &str) -> Result<Option<StoredString>, AppError> {
+        Ok(self.get_string_by_digest(&compute_sha256(value))?.filter(|found| found.value == value))
+    }
+
+    fn get_all_strings(&self, filters: &Filters) -> Result<Vec<StoredString>, AppError>;
+
+    /// Returns the page of strings matching `filters` that `pagination`
+    /// selects, in its order, plus the cursor for the next page if any.
+    fn get_page(&self, filters: &Filters, pagination: &Pagination) -> Result<(Vec<StoredString>, Option<Cursor>), AppError>;
+
+    fn delete_string(&self, value: &str) -> Result<(), AppError>;
+
+    fn string_exists(&self, hash: &str) -> Result<bool, AppError>;
+}
+
+pub type SharedStore = web::Data<dyn StringStore>;
+
+/// Rows fetched per query while streaming a listing.
+const STREAM_CHUNK: usize = 100;
+
+/// Walks the strings matching `filters` in `pagination` order one keyset
+/// page at a time, so a long listing is never held in memory at once. Stops
+/// after `pagination.limit` rows, if set.
+pub struct PagedRows {
+    store: SharedStore,
+    filters: Filters,
+    pagination: Pagination,
+    remaining: Option<usize>,
+    buffer: std::vec::IntoIter<StoredString>,
+    done: bool,
+}
+
+impl PagedRows {
+    pub fn new(store: SharedStore, filters: Filters, pagination: Pagination) -> Self {
+        PagedRows {
+            store,
+            filters,
+            remaining: pagination.limit,
+            pagination,
+            buffer: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+
+    /// Cursor for the page after the rows walked so far, once the walk is
+    /// over; `None` when nothing follows them.
+    pub fn next_cursor(&self) -> Option<&Cursor> {
+        self.pagination.cursor.as_ref()
+    }
+}
+
+impl Iterator for PagedRows {
+    type Item = Result<StoredString, AppError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(row) = self.buffer.next() {
+            return Some(Ok(row));
+        }
+        if self.done {
+            return None;
+        }
+        let chunk = Pagination {
+            limit: Some(self.remaining.map_or(STREAM_CHUNK, |remaining| remaining.min(STREAM_CHUNK))),
+            ..self.pagination.clone()
+        };
+        let (rows, next_cursor) = match self.store.get_page(&self.filters, &chunk) {
+            Ok(page) => page,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= rows.len();
+        }
+        self.done = next_cursor.is_none() || self.remaining == Some(0);
+        self.pagination.cursor = next_cursor;
+        self.buffer = rows.into_iter();
+        self.buffer.next().map(Ok)
+    }
+}
+
+pub fn build_store(config: &Config) -> io::Result<SharedStore> {
+    let store: Arc<dyn StringStore> = match config.storage_backend {
+        StorageBackend::Memory => Arc::new(InMemoryStore::new()),
+        StorageBackend::File => Arc::new(FileStore::open(&config.data_dir, config.snapshot_interval)?),
+        StorageBackend::Sqlite => {
+            std::fs::create_dir_all(&config.data_dir)?;
+            let store = SqliteStore::open(config.data_dir.join("strings.db"))
+                .map_err(io::Error::other)?;
+            Arc::new(store)
+        }
+    };
+    Ok(web::Data::from(store))
+}
+
+/// Re-analyzes a string written by an older release, whose properties may be
+/// measured differently or missing. The id, creation time, palindrome policy,
+/// requested digests and custom analyzer results are kept.
+pub(crate) fn reanalyze(string: StoredString) -> StoredString {
+    let StoredString { id, value, properties: old, created_at } = string;
+    let properties = StringProperties {
+        hashes: old.hashes,
+        extra: old.extra,
+        ..analyze_string_with_policy(&value, &old.palindrome_policy)
+    };
+    StoredString { id, value, properties, created_at }
+}
+
+pub(crate) fn apply_filters(stored: &StoredString, filters: &Filters) -> bool {
+    // Case-insensitive matching compares lowercased haystack and needles.
+    let case_insensitive = filters.case_insensitive == Some(true);
+    let folded;
+    let haystack = if case_insensitive {
+        folded = stored.value.to_lowercase();
+        folded.as_str()
+    } else {
+        stored.value.as_str()
+    };
+    let fold = |needle: &str| if case_insensitive { needle.to_lowercase() } else { needle.to_string() };
+    let contains = |needle: &str| haystack.contains(fold(needle).as_str());
+
+    if let Some(is_pal) = filters.is_palindrome {
+        if stored.properties.is_palindrome != is_pal {
+            return false;
+        }
+    }
+    if let Some(min) = filters.min_length {
+        if stored.properties.length < min {
+            return false;
+        }
+    }
+    if let Some(max) = filters.max_length {
+        if stored.properties.length > max {
+            return false;
+        }
+    }
+    if let Some(wc) = filters.word_count {
+        if stored.properties.word_count != wc {
+            return false;
+        }
+    }
+    if let Some(ch) = &filters.contains_character {
+        if !contains(ch) {
+            return false;
+        }
+    }
+    if let Some(graphemes) = &filters.contains_characters {
+        let mut found = graphemes.iter().map(|grapheme| contains(grapheme));
+        let matched = match filters.contains_mode.unwrap_or_default() {
+            ContainsMode::All => found.all(|f| f),
+            ContainsMode::Any => found.any(|f| f),
+        };
+        if !matched {
+            return false;
+        }
+    }
+    if let Some(sub) = &filters.contains {
+        if !contains(sub) {
+            return false;
+        }
+    }
+    if let Some(prefix) = &filters.starts_with {
+        if !haystack.starts_with(fold(prefix).as_str()) {
+            return false;
+        }
+    }
+    if let Some(suffix) = &filters.ends_with {
+        if !haystack.ends_with(fold(suffix).as_str()) {
+            return false;
+        }
+    }
+    if let Some(pattern) = &filters.matches {
+        if !pattern.is_match(&stored.value) {
+            return false;
+        }
+    }
+    if let Some(min) = filters.min_word_count {
+        if stored.properties.word_count < min {
+            return false;
+        }
+    }
+    if let Some(max) = filters.max_word_count {
+        if stored.properties.word_count > max {
+            return false;
+        }
+    }
+    if let Some(min) = filters.min_unique_characters {
+        if stored.properties.unique_characters < min {
+            return false;
+        }
+    }
+    if let Some(max) = filters.max_unique_characters {
+        if stored.properties.unique_characters > max {
+            return false;
+        }
+    }
+    if let Some(after) = filters.created_after {
+        if stored.created_at < after {
+            return false;
+        }
+    }
+    if let Some(before) = filters.created_before {
+        if stored.created_at > before {
+            return false;
+        }
+    }
+    if let Some(expr) = &filters.filter {
+        if !expr.matches(stored) {
+            return false;
+        }
+    }
+    true
+}
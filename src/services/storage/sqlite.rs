@@ -0,0 +1,384 @@
+use crate::error::AppError;
+use crate::models::string::{StoredString, Filters, ContainsMode, SortKey, SortOrder};
+use crate::services::analyzer::analyze_string;
+use crate::services::hashing::{compute_sha256, HashAlgorithm};
+use crate::utils::pagination::{finish_page, sort_value, Cursor, Pagination};
+use super::{StringStore, apply_filters, reanalyze};
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::Mutex;
+
+const SCHEMA_VERSION: i64 = 5;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS strings (
+        id TEXT PRIMARY KEY,
+        value TEXT NOT NULL,
+        length INTEGER NOT NULL,
+        is_palindrome INTEGER NOT NULL,
+        unique_characters INTEGER NOT NULL,
+        word_count INTEGER NOT NULL,
+        properties TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        created_micros INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_strings_length ON strings(length, id);
+    CREATE INDEX IF NOT EXISTS idx_strings_word_count ON strings(word_count, id);
+    CREATE INDEX IF NOT EXISTS idx_strings_unique_characters ON strings(unique_characters, id);
+    CREATE INDEX IF NOT EXISTS idx_strings_created ON strings(created_micros, id);
+    CREATE INDEX IF NOT EXISTS idx_strings_is_palindrome ON strings(is_palindrome);
+    CREATE TABLE IF NOT EXISTS digests (
+        digest TEXT NOT NULL,
+        algorithm TEXT NOT NULL,
+        id TEXT NOT NULL,
+        PRIMARY KEY (digest, algorithm)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS idx_digests_id ON digests(id);
+";
+
+const INSERT: &str = "INSERT OR IGNORE INTO strings (id, value, length, is_palindrome, unique_characters, \
+     word_count, properties, created_at, created_micros) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
+
+const INSERT_DIGEST: &str = "INSERT OR IGNORE INTO digests (digest, algorithm, id) VALUES (?1, ?2, ?3)";
+
+const SELECT_COLUMNS: &str = "SELECT id, value, properties, created_at FROM strings";
+
+/// Embedded SQLite store. The full `StringProperties` are kept as JSON, and
+/// the filterable ones are duplicated into indexed columns so that `Filters`
+/// can be pushed down into a WHERE clause, and every sort key is indexed
+/// together with the id so that a page is one keyset range. Every digest of a string (SHA-256
+/// plus `hashes`) is mapped to its id in the `digests` table, keyed by digest
+/// and algorithm.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> rusqlite::Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        migrate(&mut conn)?;
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(SqliteStore {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+/// Upgrades older databases by rebuilding both tables and re-analyzing their
+/// rows with the current analyzer. Version 0 predates the `properties` column
+/// and is rebuilt from the values; later versions keep their requested
+/// digests. Versions before 3 lack the character class counts, before 4 the
+/// per-algorithm `digests` table and before 5 the `created_micros` column.
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    let has_table = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'strings'", [], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if version >= SCHEMA_VERSION || !has_table {
+        return Ok(());
+    }
+
+    log::info!("Migrating SQLite schema from version {} to {}", version, SCHEMA_VERSION);
+    let tx = conn.transaction()?;
+    if version < 1 {
+        let rows: Vec<(String, String, DateTime<Utc>)> = {
+            let mut stmt = tx.prepare("SELECT id, value, created_at FROM strings")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        tx.execute_batch("DROP TABLE strings;")?;
+        tx.execute_batch(SCHEMA)?;
+        for (id, value, created_at) in rows {
+            let properties = analyze_string(&value);
+            insert(&tx, &StoredString { id, value, properties, created_at })?;
+        }
+    } else {
+        let strings: Vec<StoredString> = {
+            let mut stmt = tx.prepare(SELECT_COLUMNS)?;
+            let rows = stmt.query_map([], row_to_stored)?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        tx.execute_batch("DROP TABLE strings; DROP TABLE IF EXISTS digests;")?;
+        tx.execute_batch(SCHEMA)?;
+        for string in strings {
+            insert(&tx, &reanalyze(string))?;
+        }
+    }
+    tx.commit()
+}
+
+/// Inserts `string` and its digests. Returns false, writing nothing, when the
+/// id is already stored or one of its digests belongs to another string.
+fn insert(conn: &Connection, string: &StoredString) -> rusqlite::Result<bool> {
+    let mut owner = conn.prepare_cached("SELECT id FROM digests WHERE digest = ?1 AND algorithm = ?2")?;
+    for (algorithm, digest) in string.digests() {
+        let id: Option<String> = owner.query_row([digest, algorithm.name()], |row| row.get(0)).optional()?;
+        if id.is_some_and(|id| id != string.id) {
+            return Ok(false);
+        }
+    }
+
+    let properties = serde_json::to_string(&string.properties)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let inserted = conn.execute(
+        INSERT,
+        params![
+            string.id,
+            string.value,
+            string.properties.length as i64,
+            string.properties.is_palindrome,
+            string.properties.unique_characters as i64,
+            string.properties.word_count as i64,
+            properties,
+            string.created_at,
+            string.created_at.timestamp_micros(),
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+    insert_digests(conn, string)?;
+    Ok(true)
+}
+
+fn insert_digests(conn: &Connection, string: &StoredString) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(INSERT_DIGEST)?;
+    for (algorithm, digest) in string.digests() {
+        stmt.execute([digest, algorithm.name(), &string.id])?;
+    }
+    Ok(())
+}
+
+impl StringStore for SqliteStore {
+    fn save_string(&self, string: StoredString) -> Result<(), AppError> {
+        let conn = self.conn.lock().unwrap();
+        if !insert(&conn, &string)? {
+            return Err(AppError::AlreadyExists("String already exists".to_string()));
+        }
+        Ok(())
+    }
+
+    fn save_all(&self, strings: Vec<StoredString>) -> Result<(), AppError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for string in &strings {
+            if !insert(&tx, string)? {
+                // Dropping the transaction rolls back everything inserted so far.
+                return Err(AppError::AlreadyExists(format!("String already exists: {}", string.value)));
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    fn get_string_by_hash(&self, hash: &str) -> Result<Option<StoredString>, AppError> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!("{} WHERE id = ?1", SELECT_COLUMNS);
+        Ok(conn.query_row(&sql, [hash], row_to_stored).optional()?)
+    }
+
+    fn get_string_by_digest(&self, digest: &str) -> Result<Option<StoredString>, AppError> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!(
+            "{} WHERE id IN (?1, (SELECT id FROM digests WHERE digest = ?1)) LIMIT 1",
+            SELECT_COLUMNS
+        );
+        Ok(conn.query_row(&sql, [digest.to_lowercase()], row_to_stored).optional()?)
+    }
+
+    fn get_all_strings(&self, filters: &Filters) -> Result<Vec<StoredString>, AppError> {
+        let conn = self.conn.lock().unwrap();
+        let (conditions, values) = filters_to_sql(filters);
+        let sql = format!("{}{}", SELECT_COLUMNS, where_clause(&conditions));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params_from_iter(values), row_to_stored)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        // Anything the WHERE clause could not express is still checked here.
+        Ok(rows.into_iter().filter(|s| apply_filters(s, filters)).collect())
+    }
+
+    fn get_page(&self, filters: &Filters, pagination: &Pagination) -> Result<(Vec<StoredString>, Option<Cursor>), AppError> {
+        let conn = self.conn.lock().unwrap();
+        let (conditions, values) = filters_to_sql(filters);
+        let column = sort_column(pagination.sort_by);
+        let (after_op, direction) = match pagination.order {
+            SortOrder::Asc => (">", "ASC"),
+            SortOrder::Desc => ("<", "DESC"),
+        };
+        let fetch = pagination.fetch_limit();
+        let mut after = pagination.after().map(|(key, id)| (key, id.to_string()));
+        let mut rows = Vec::new();
+
+        // Rows failing the residual check take no place on the page, so reading
+        // resumes after the last row seen until the page is full.
+        loop {
+            let wanted = fetch.map(|fetch| fetch - rows.len());
+            let mut conditions = conditions.clone();
+            let mut values = values.clone();
+            if let Some((key, id)) = &after {
+                conditions.push(format!("({}, id) {} (?, ?)", column, after_op));
+                values.push(Value::Integer(*key));
+                values.push(Value::Text(id.clone()));
+            }
+            values.push(Value::Integer(wanted.map_or(-1, |wanted| wanted as i64)));
+            let sql = format!(
+                "{}{} ORDER BY {2} {3}, id {3} LIMIT ?",
+                SELECT_COLUMNS,
+                where_clause(&conditions),
+                column,
+                direction
+            );
+
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let batch = stmt
+                .query_map(params_from_iter(values), row_to_stored)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let exhausted = wanted.is_none_or(|wanted| batch.len() < wanted);
+            if let Some(last) = batch.last() {
+                after = Some((sort_value(last, pagination.sort_by), last.id.clone()));
+            }
+            rows.extend(batch.into_iter().filter(|s| apply_filters(s, filters)));
+            if exhausted || fetch.is_some_and(|fetch| rows.len() >= fetch) {
+                break;
+            }
+        }
+        Ok(finish_page(rows, pagination))
+    }
+
+    fn delete_string(&self, value: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let id: Option<String> = tx
+            .query_row(
+                "SELECT id FROM strings WHERE value = ?3 \
+                 AND id = (SELECT id FROM digests WHERE digest = ?1 AND algorithm = ?2)",
+                [compute_sha256(value).as_str(), HashAlgorithm::Sha256.name(), value],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(id) = id else {
+            return Err(AppError::NotFound("String not found".to_string()));
+        };
+        tx.execute("DELETE FROM strings WHERE id = ?1", [&id])?;
+        tx.execute("DELETE FROM digests WHERE id = ?1", [&id])?;
+        Ok(tx.commit()?)
+    }
+
+    fn string_exists(&self, hash: &str) -> Result<bool, AppError> {
+        let conn = self.conn.lock().unwrap();
+        let found = conn.query_row("SELECT 1 FROM strings WHERE id = ?1", [hash], |_| Ok(())).optional()?;
+        Ok(found.is_some())
+    }
+}
+
+/// Indexed column holding the value `key` sorts by.
+fn sort_column(key: SortKey) -> &'static str {
+    match key {
+        SortKey::CreatedAt => "created_micros",
+        SortKey::Length => "length",
+        SortKey::WordCount => "word_count",
+        SortKey::UniqueCharacters => "unique_characters",
+    }
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    }
+}
+
+/// Translates what it can of `filters` into WHERE conditions and their
+/// parameters. The rest is left to `apply_filters`.
+fn filters_to_sql(filters: &Filters) -> (Vec<String>, Vec<Value>) {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values = Vec::new();
+
+    if let Some(is_pal) = filters.is_palindrome {
+        conditions.push("is_palindrome = ?".to_string());
+        values.push(Value::Integer(is_pal as i64));
+    }
+    // instr() and substr() comparisons are case-sensitive; case-insensitive
+    // text filters are left to the residual check.
+    if filters.case_insensitive != Some(true) {
+        if let Some(ch) = &filters.contains_character {
+            conditions.push("instr(value, ?) > 0".to_string());
+            values.push(Value::Text(ch.clone()));
+        }
+        if let Some(graphemes) = &filters.contains_characters {
+            let joiner = match filters.contains_mode.unwrap_or_default() {
+                ContainsMode::All => " AND ",
+                ContainsMode::Any => " OR ",
+            };
+            let parts = vec!["instr(value, ?) > 0"; graphemes.len()];
+            conditions.push(format!("({})", parts.join(joiner)));
+            values.extend(graphemes.iter().cloned().map(Value::Text));
+        }
+        if let Some(sub) = &filters.contains {
+            conditions.push("instr(value, ?) > 0".to_string());
+            values.push(Value::Text(sub.clone()));
+        }
+        if let Some(prefix) = &filters.starts_with {
+            conditions.push("substr(value, 1, length(?)) = ?".to_string());
+            values.push(Value::Text(prefix.clone()));
+            values.push(Value::Text(prefix.clone()));
+        }
+        if let Some(suffix) = &filters.ends_with {
+            conditions.push("substr(value, -length(?)) = ?".to_string());
+            values.push(Value::Text(suffix.clone()));
+            values.push(Value::Text(suffix.clone()));
+        }
+    }
+    // Counts past i64::MAX cannot be stored, so such an upper bound is always
+    // met and a lower or exact one never is.
+    for (condition, bound, is_upper) in [
+        ("length >= ?", filters.min_length, false),
+        ("length <= ?", filters.max_length, true),
+        ("word_count = ?", filters.word_count, false),
+        ("word_count >= ?", filters.min_word_count, false),
+        ("word_count <= ?", filters.max_word_count, true),
+        ("unique_characters >= ?", filters.min_unique_characters, false),
+        ("unique_characters <= ?", filters.max_unique_characters, true),
+    ] {
+        let Some(bound) = bound else { continue };
+        match i64::try_from(bound) {
+            Ok(bound) => {
+                conditions.push(condition.to_string());
+                values.push(Value::Integer(bound));
+            }
+            Err(_) if is_upper => {}
+            Err(_) => conditions.push("FALSE".to_string()),
+        }
+    }
+
+    (conditions, values)
+}
+
+fn row_to_stored(row: &Row) -> rusqlite::Result<StoredString> {
+    let properties: String = row.get(2)?;
+    let properties = serde_json::from_str(&properties).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok(StoredString {
+        id: row.get(0)?,
+        value: row.get(1)?,
+        properties,
+        created_at: row.get(3)?,
+    })
+}
@@ -0,0 +1,246 @@
+use crate::models::string::{StoredString, StringProperties};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Columns written by the CSV and columnar exports. The scalar properties are
+/// spelled out for spreadsheets and analytics tools; `properties` carries the
+/// full JSON so imports are lossless.
+pub const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "value",
+    "created_at",
+    "length",
+    "byte_length",
+    "code_point_count",
+    "grapheme_count",
+    "is_palindrome",
+    "unique_characters",
+    "word_count",
+    "vowel_count",
+    "consonant_count",
+    "digit_count",
+    "punctuation_count",
+    "sha256_hash",
+    "properties",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferFormat {
+    #[default]
+    Ndjson,
+    Csv,
+    /// One JSON object of column arrays, `{"id": [...], "value": [...], ...}`,
+    /// laid out like a Parquet file so it loads straight into a data frame.
+    Columnar,
+}
+
+impl TransferFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "ndjson" | "jsonl" => Ok(TransferFormat::Ndjson),
+            "csv" => Ok(TransferFormat::Csv),
+            "columnar" => Ok(TransferFormat::Columnar),
+            _ => Err(format!("Unsupported format: {}. Use ndjson, csv or columnar", s)),
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            TransferFormat::Ndjson => "application/x-ndjson",
+            TransferFormat::Csv => "text/csv; charset=utf-8",
+            TransferFormat::Columnar => "application/json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            TransferFormat::Ndjson => "ndjson",
+            TransferFormat::Csv => "csv",
+            TransferFormat::Columnar => "json",
+        }
+    }
+}
+
+/// What to do with properties supplied in an import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    /// Recompute everything from the value with the server's settings.
+    #[default]
+    Reanalyze,
+    /// Keep the supplied properties as they are. A supplied id and every
+    /// supplied digest must be the ones this server derives from the value.
+    Trust,
+}
+
+impl ImportMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "reanalyze" => Ok(ImportMode::Reanalyze),
+            "trust" => Ok(ImportMode::Trust),
+            _ => Err(format!("Unsupported import mode: {}. Use reanalyze or trust", s)),
+        }
+    }
+}
+
+/// One imported item. Only `value` is required; the rest is what an export
+/// produces and is used according to the `ImportMode`.
+#[derive(Debug, Deserialize)]
+pub struct ImportRecord {
+    pub value: String,
+    pub id: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub properties: Option<StringProperties>,
+}
+
+pub fn ndjson_line(string: &StoredString) -> Result<Vec<u8>, String> {
+    let mut line = serde_json::to_vec(string).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+pub fn csv_header() -> Result<Vec<u8>, String> {
+    csv_row(CSV_COLUMNS.iter().map(|c| c.to_string()))
+}
+
+pub fn csv_line(string: &StoredString) -> Result<Vec<u8>, String> {
+    let props = &string.properties;
+    let properties = serde_json::to_string(props).map_err(|e| e.to_string())?;
+    csv_row([
+        string.id.clone(),
+        string.value.clone(),
+        string.created_at.to_rfc3339(),
+        props.length.to_string(),
+        props.byte_length.to_string(),
+        props.code_point_count.to_string(),
+        props.grapheme_count.to_string(),
+        props.is_palindrome.to_string(),
+        props.unique_characters.to_string(),
+        props.word_count.to_string(),
+        props.vowel_count.to_string(),
+        props.consonant_count.to_string(),
+        props.digit_count.to_string(),
+        props.punctuation_count.to_string(),
+        props.sha256_hash.clone(),
+        properties,
+    ])
+}
+
+/// The whole export as one object of `CSV_COLUMNS` arrays. Unlike the other
+/// formats it cannot be written row by row.
+pub fn columnar_body(strings: &[StoredString]) -> Result<Vec<u8>, String> {
+    let mut columns: Vec<Vec<Value>> = vec![Vec::with_capacity(strings.len()); CSV_COLUMNS.len()];
+    for string in strings {
+        let props = &string.properties;
+        let properties = serde_json::to_value(props).map_err(|e| e.to_string())?;
+        let cells: [Value; 16] = [
+            string.id.clone().into(),
+            string.value.clone().into(),
+            string.created_at.to_rfc3339().into(),
+            props.length.into(),
+            props.byte_length.into(),
+            props.code_point_count.into(),
+            props.grapheme_count.into(),
+            props.is_palindrome.into(),
+            props.unique_characters.into(),
+            props.word_count.into(),
+            props.vowel_count.into(),
+            props.consonant_count.into(),
+            props.digit_count.into(),
+            props.punctuation_count.into(),
+            props.sha256_hash.clone().into(),
+            properties,
+        ];
+        for (column, cell) in columns.iter_mut().zip(cells) {
+            column.push(cell);
+        }
+    }
+    let object: Map<String, Value> = CSV_COLUMNS
+        .iter()
+        .map(|name| name.to_string())
+        .zip(columns.into_iter().map(Value::Array))
+        .collect();
+    serde_json::to_vec(&object).map_err(|e| e.to_string())
+}
+
+fn csv_row(fields: impl IntoIterator<Item = String>) -> Result<Vec<u8>, String> {
+    let mut writer = csv::WriterBuilder::new().from_writer(Vec::new());
+    writer.write_record(fields).map_err(|e| e.to_string())?;
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+/// Splits an import body into records. A malformed record becomes an `Err`
+/// in its slot so the caller can report it by index; a body that cannot be
+/// read at all is an error of its own.
+pub fn parse_import(body: &[u8], format: TransferFormat) -> Result<Vec<Result<ImportRecord, String>>, String> {
+    let text = std::str::from_utf8(body).map_err(|_| "Request body is not valid UTF-8".to_string())?;
+    match format {
+        TransferFormat::Ndjson => Ok(text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| format!("Invalid record: {}", e)))
+            .collect()),
+        TransferFormat::Csv => parse_csv(text),
+        TransferFormat::Columnar => parse_columnar(text),
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Result<ImportRecord, String>>, String> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Invalid CSV header: {}", e))?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let value_column = column("value").ok_or("CSV must have a value column")?;
+    let (id_column, created_column, properties_column) = (column("id"), column("created_at"), column("properties"));
+
+    Ok(reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| format!("Invalid CSV row: {}", e))?;
+            let field = |index: Option<usize>| index.and_then(|i| record.get(i)).filter(|f| !f.is_empty());
+            let value = record.get(value_column).ok_or("Missing value")?.to_string();
+            let created_at = field(created_column)
+                .map(|s| DateTime::parse_from_rfc3339(s).map(|d| d.with_timezone(&Utc)))
+                .transpose()
+                .map_err(|e| format!("Invalid created_at: {}", e))?;
+            let properties = field(properties_column)
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(|e| format!("Invalid properties: {}", e))?;
+            Ok(ImportRecord {
+                value,
+                id: field(id_column).map(str::to_string),
+                created_at,
+                properties,
+            })
+        })
+        .collect())
+}
+
+fn parse_columnar(text: &str) -> Result<Vec<Result<ImportRecord, String>>, String> {
+    let columns: HashMap<String, Vec<Value>> =
+        serde_json::from_str(text).map_err(|e| format!("Invalid columnar body: {}", e))?;
+    let values = columns.get("value").ok_or("Columnar body must have a value column")?;
+    if let Some((name, _)) = columns.iter().find(|(_, column)| column.len() != values.len()) {
+        return Err(format!("Column {} has a different length than value", name));
+    }
+    let cell = |name: &str, index: usize| columns.get(name).map(|c| &c[index]).filter(|v| !v.is_null());
+
+    Ok((0..values.len())
+        .map(|index| {
+            let value = values[index].as_str().ok_or("value must be a string")?.to_string();
+            let id = cell("id", index)
+                .map(|id| id.as_str().map(str::to_string).ok_or("id must be a string"))
+                .transpose()?;
+            let created_at = cell("created_at", index)
+                .map(DateTime::<Utc>::deserialize)
+                .transpose()
+                .map_err(|e| format!("Invalid created_at: {}", e))?;
+            let properties = cell("properties", index)
+                .map(StringProperties::deserialize)
+                .transpose()
+                .map_err(|e| format!("Invalid properties: {}", e))?;
+            Ok(ImportRecord { value, id, created_at, properties })
+        })
+        .collect())
+}
@@ -1,6 +1,28 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use crate::services::analyzer::analyze_string;
+    use crate::models::string::{CaseMode, PalindromePolicy};
+    use crate::services::analyzer::{analyze_string, analyze_string_with_policy, AnalysisSettings};
+    use crate::services::analyzer_registry::{Analyzer, AnalyzerRegistry};
+    use crate::services::hashing::{parse_algorithms, HashAlgorithm};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    struct ShoutAnalyzer;
+
+    impl Analyzer for ShoutAnalyzer {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn version(&self) -> &str {
+            "0.1.0"
+        }
+
+        fn analyze(&self, value: &str) -> Value {
+            json!(value.to_uppercase())
+        }
+    }
 
     #[test]
     fn test_length() {
@@ -60,9 +82,172 @@ mod tests {
     #[test]
     fn test_character_frequency() {
         let result = analyze_string("hello");
-        assert_eq!(result.character_frequency_map.get(&'h'), Some(&1));
-        assert_eq!(result.character_frequency_map.get(&'e'), Some(&1));
-        assert_eq!(result.character_frequency_map.get(&'l'), Some(&2));
-        assert_eq!(result.character_frequency_map.get(&'o'), Some(&1));
+        assert_eq!(result.character_frequency_map.get("h"), Some(&1));
+        assert_eq!(result.character_frequency_map.get("e"), Some(&1));
+        assert_eq!(result.character_frequency_map.get("l"), Some(&2));
+        assert_eq!(result.character_frequency_map.get("o"), Some(&1));
+    }
+
+    #[test]
+    fn test_lengths_for_combining_marks() {
+        let result = analyze_string("cafe\u{301}");
+        assert_eq!(result.length, 4);
+        assert_eq!(result.grapheme_count, 4);
+        assert_eq!(result.code_point_count, 5);
+        assert_eq!(result.byte_length, 6);
+        assert_eq!(result.character_frequency_map.get("e\u{301}"), Some(&1));
+    }
+
+    #[test]
+    fn test_character_class_counts() {
+        let result = analyze_string("Éclair, 2 rhythms!");
+        assert_eq!(result.vowel_count, 3);
+        assert_eq!(result.consonant_count, 10);
+        assert_eq!(result.digit_count, 1);
+        assert_eq!(result.punctuation_count, 2);
+    }
+
+    #[test]
+    fn test_zwj_emoji_is_one_grapheme() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let result = analyze_string(family);
+        assert_eq!(result.length, 1);
+        assert_eq!(result.code_point_count, 5);
+        assert_eq!(result.unique_characters, 1);
+    }
+
+    #[test]
+    fn test_palindrome_with_combining_marks() {
+        let result = analyze_string("e\u{301}te\u{301}");
+        assert!(result.is_palindrome);
+        let result = analyze_string("e\u{301}ta");
+        assert!(!result.is_palindrome);
+    }
+
+    #[test]
+    fn test_palindrome_ignoring_punctuation() {
+        let phrase = "A man, a plan, a canal: Panama";
+        assert!(!analyze_string(phrase).is_palindrome);
+
+        let policy = PalindromePolicy {
+            ignore_punctuation: true,
+            ..PalindromePolicy::default()
+        };
+        let result = analyze_string_with_policy(phrase, &policy);
+        assert!(result.is_palindrome);
+        assert_eq!(result.palindrome_policy, policy);
+    }
+
+    #[test]
+    fn test_palindrome_ignoring_diacritics() {
+        let policy = PalindromePolicy {
+            ignore_diacritics: true,
+            ..PalindromePolicy::default()
+        };
+        assert!(!analyze_string("ésse").is_palindrome);
+        assert!(analyze_string_with_policy("ésse", &policy).is_palindrome);
+    }
+
+    #[test]
+    fn test_palindrome_case_modes() {
+        let sensitive = PalindromePolicy {
+            case_mode: CaseMode::Sensitive,
+            ..PalindromePolicy::default()
+        };
+        assert!(!analyze_string_with_policy("RaceCar", &sensitive).is_palindrome);
+
+        let fold = PalindromePolicy {
+            case_mode: CaseMode::Fold,
+            ..PalindromePolicy::default()
+        };
+        assert!(analyze_string_with_policy("ßS", &fold).is_palindrome);
+        assert!(!analyze_string("ßS").is_palindrome);
+        // `ŉ` folds to `ʼn`, which no lowercase mapping produces.
+        assert!(analyze_string_with_policy("ŉNʼ", &fold).is_palindrome);
+        assert!(!analyze_string("ŉNʼ").is_palindrome);
+    }
+
+    #[test]
+    fn test_registered_analyzer_output_is_stored() {
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(Arc::new(ShoutAnalyzer)).unwrap();
+        let settings = AnalysisSettings {
+            registry,
+            ..AnalysisSettings::default()
+        };
+
+        let result = settings.analyze("hey", &PalindromePolicy::default(), &[]);
+        assert_eq!(result.extra.get("shout"), Some(&json!("HEY")));
+
+        let serialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(serialized["shout"], "HEY");
+        assert_eq!(serialized["length"], 3);
+    }
+
+    #[test]
+    fn test_registry_rejects_conflicting_names() {
+        struct Impostor;
+
+        impl Analyzer for Impostor {
+            fn name(&self) -> &str {
+                "length"
+            }
+
+            fn version(&self) -> &str {
+                "1.0.0"
+            }
+
+            fn analyze(&self, _value: &str) -> Value {
+                Value::Null
+            }
+        }
+
+        let mut registry = AnalyzerRegistry::new();
+        assert!(registry.register(Arc::new(Impostor)).is_err());
+        registry.register(Arc::new(ShoutAnalyzer)).unwrap();
+        assert!(registry.register(Arc::new(ShoutAnalyzer)).is_err());
+        assert!(AnalyzerRegistry::with_enabled(&["nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_default_analyzers() {
+        let result = AnalysisSettings::default().analyze("aabb", &PalindromePolicy::default(), &[]);
+        assert_eq!(result.extra.get("entropy"), Some(&json!(1.0)));
+
+        let result = AnalysisSettings::default().analyze("ab cdef", &PalindromePolicy::default(), &[]);
+        assert_eq!(result.extra.get("average_word_length"), Some(&json!(3.0)));
+    }
+
+    #[test]
+    fn test_hash_algorithms() {
+        assert_eq!(HashAlgorithm::Sha1.digest("abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(HashAlgorithm::Crc32.digest("abc"), "352441c2");
+        assert_eq!(
+            HashAlgorithm::Blake3.digest("abc"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(HashAlgorithm::Xxhash.digest("abc").len(), 16);
+        assert_eq!(HashAlgorithm::Sha512.digest("abc").len(), 128);
+
+        assert_eq!(
+            parse_algorithms("blake3, sha512,blake3").unwrap(),
+            vec![HashAlgorithm::Blake3, HashAlgorithm::Sha512]
+        );
+        assert!(parse_algorithms("md5").is_err());
+    }
+
+    #[test]
+    fn test_requested_hashes_include_id_algorithm() {
+        let settings = AnalysisSettings {
+            id_algorithm: HashAlgorithm::Blake3,
+            ..AnalysisSettings::default()
+        };
+        let result = settings.analyze("abc", &PalindromePolicy::default(), &[HashAlgorithm::Crc32]);
+        assert_eq!(result.hashes.len(), 2);
+        assert_eq!(result.hashes[&HashAlgorithm::Crc32], "352441c2");
+        assert_eq!(result.hashes[&HashAlgorithm::Blake3], settings.id_for("abc"));
+
+        let serialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(serialized["hashes"]["crc32"], "352441c2");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::stored;
+    use crate::utils::filters::expr::FilterExpr;
+
+    #[test]
+    fn test_precedence_and_grouping() {
+        let expr = FilterExpr::parse("(length > 10 AND NOT is_palindrome) OR word_count IN (1,2)").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "((length > 10 AND NOT is_palindrome = true) OR word_count IN (1, 2))"
+        );
+
+        assert!(expr.matches(&stored("racecar")));
+        assert!(expr.matches(&stored("a long sentence of words")));
+        assert!(!expr.matches(&stored("was it a car or a cat i saw")));
+    }
+
+    #[test]
+    fn test_keywords_are_case_insensitive() {
+        let expr = FilterExpr::parse("value contains 'ell' and length <= 5").unwrap();
+        assert!(expr.matches(&stored("hello")));
+        assert!(!expr.matches(&stored("hello world")));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let expr = FilterExpr::parse("first_character IS vowel AND digit_count = 0").unwrap();
+        assert_eq!(expr.to_string(), "(first_character IS vowel AND digit_count = 0)");
+        assert!(expr.matches(&stored("Élan")));
+        assert!(!expr.matches(&stored("apollo 11")));
+        assert!(!expr.matches(&stored("banana")));
+
+        let expr = FilterExpr::parse("last_character is punctuation").unwrap();
+        assert!(expr.matches(&stored("done.")));
+        assert!(!expr.matches(&stored("")));
+
+        assert_eq!(FilterExpr::parse("first_character = 'a'").unwrap_err().column, 17);
+        assert_eq!(FilterExpr::parse("last_character IS letter").unwrap_err().column, 19);
+    }
+
+    #[test]
+    fn test_error_points_at_column() {
+        let err = FilterExpr::parse("length > 10 AND colour = 3").unwrap_err();
+        assert_eq!(err.column, 17);
+
+        let err = FilterExpr::parse("(length > 10").unwrap_err();
+        assert_eq!(err.column, 13);
+
+        let err = FilterExpr::parse("is_palindrome = 3").unwrap_err();
+        assert_eq!(err.column, 17);
+    }
+
+    #[test]
+    fn test_rejects_ordering_on_booleans() {
+        let err = FilterExpr::parse("is_palindrome > true").unwrap_err();
+        assert_eq!(err.column, 15);
+    }
+
+    #[test]
+    fn test_rejects_excessive_nesting() {
+        let input = format!("{}length = 1{}", "(".repeat(100), ")".repeat(100));
+        assert!(FilterExpr::parse(&input).is_err());
+    }
+}
@@ -1,15 +1,39 @@
 #[cfg(test)]
 mod tests {
-    use actix_web::{test, web, App, http::StatusCode};
-    use crate::routes::string_routes;
-    use crate::models::string::{StringInput, StoredString};
+    use actix_web::dev::{Service, ServiceResponse};
+    use actix_web::{test, web, App, HttpResponse, http::StatusCode};
+    use crate::error::AppError;
+    use crate::middleware::JsonErrorHandler;
+    use crate::routes::{analyzer_routes, string_routes};
+    use crate::services::analyzer::AnalysisSettings;
+    use crate::services::hashing::compute_sha256;
+    use crate::services::nlp_parser::Lexicons;
+    use crate::services::storage::{InMemoryStore, SharedStore, StringStore};
     use serde_json::json;
+    use std::sync::Arc;
+
+    fn test_store() -> SharedStore {
+        let store: Arc<dyn StringStore> = Arc::new(InMemoryStore::new());
+        web::Data::from(store)
+    }
+
+    macro_rules! test_app {
+        () => {
+            test::init_service(
+                App::new()
+                    .app_data(test_store())
+                    .app_data(web::Data::new(AnalysisSettings::default()))
+                    .app_data(web::Data::new(Lexicons::builtin()))
+                    .wrap(JsonErrorHandler)
+                    .configure(string_routes::configure)
+                    .configure(analyzer_routes::configure)
+            ).await
+        };
+    }
 
     #[actix_web::test]
     async fn test_create_string_success() {
-        let app = test::init_service(
-            App::new().configure(string_routes::configure)
-        ).await;
+        let app = test_app!();
 
         let payload = json!({
             "value": "test string"
@@ -26,9 +50,7 @@ mod tests {
 
     #[actix_web::test]
     async fn test_create_string_duplicate() {
-        let app = test::init_service(
-            App::new().configure(string_routes::configure)
-        ).await;
+        let app = test_app!();
 
         let payload = json!({
             "value": "duplicate test"
@@ -51,9 +73,7 @@ mod tests {
 
     #[actix_web::test]
     async fn test_get_string_not_found() {
-        let app = test::init_service(
-            App::new().configure(string_routes::configure)
-        ).await;
+        let app = test_app!();
 
         let req = test::TestRequest::get()
             .uri("/strings/nonexistent")
@@ -65,9 +85,7 @@ mod tests {
 
     #[actix_web::test]
     async fn test_delete_string_success() {
-        let app = test::init_service(
-            App::new().configure(string_routes::configure)
-        ).await;
+        let app = test_app!();
 
         let payload = json!({
             "value": "to delete"
@@ -89,9 +107,7 @@ mod tests {
 
     #[actix_web::test]
     async fn test_filter_palindromes() {
-        let app = test::init_service(
-            App::new().configure(string_routes::configure)
-        ).await;
+        let app = test_app!();
 
         let req = test::TestRequest::get()
             .uri("/strings?is_palindrome=true")
@@ -103,9 +119,7 @@ mod tests {
 
     #[actix_web::test]
     async fn test_natural_language_query() {
-        let app = test::init_service(
-            App::new().configure(string_routes::configure)
-        ).await;
+        let app = test_app!();
 
         let req = test::TestRequest::get()
             .uri("/strings/filter-by-natural-language?query=all%20single%20word%20palindromic%20strings")
@@ -113,5 +127,989 @@ mod tests {
 
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let interpreted = &body["interpreted_query"];
+        assert_eq!(interpreted["confidence"], 1.0);
+        assert_eq!(interpreted["clauses"][0]["filter"], "word_count = 1");
+        assert_eq!(interpreted["clauses"][1]["text"], "palindromic");
+        assert_eq!(interpreted["ignored_words"], json!([]));
+    }
+
+    #[actix_web::test]
+    async fn test_natural_language_sort_and_limit() {
+        let app = test_app!();
+
+        for value in ["abba", "racecar", "level", "a", "not one"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=the%202%20longest%20palindromes")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["data"][0]["value"], "racecar");
+        assert_eq!(body["data"][1]["value"], "level");
+        assert_eq!(body["interpreted_query"]["sort_by"], "length");
+        assert_eq!(body["interpreted_query"]["order"], "desc");
+        assert_eq!(body["interpreted_query"]["limit"], 2);
+    }
+
+    #[actix_web::test]
+    async fn test_natural_language_query_languages() {
+        let app = test_app!();
+
+        for value in ["reconocer", "radar", "hello"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=pal%C3%ADndromos%20con%20la%20letra%20c")
+            .insert_header(("accept-language", "es-ES,es;q=0.9,en;q=0.8"))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["data"][0]["value"], "reconocer");
+        assert_eq!(body["interpreted_query"]["language"], "es");
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=palindromes&lang=fr")
+            .insert_header(("accept-language", "de"))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["interpreted_query"]["language"], "fr");
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=palindromes&lang=tlh")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["invalid_params"][0]["name"], "lang");
+    }
+
+    #[actix_web::test]
+    async fn test_stores_are_isolated_per_app() {
+        let first = test_app!();
+        let second = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "isolated" }))
+            .to_request();
+        let resp = test::call_service(&first, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/isolated")
+            .to_request();
+        let resp = test::call_service(&second, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_list_pagination_with_cursor() {
+        let app = test_app!();
+
+        for value in ["aaaa", "a", "aaa", "aa", "aaaaa"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?sort_by=length&order=desc&limit=2")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["data"][0]["value"], "aaaaa");
+        assert_eq!(body["data"][1]["value"], "aaaa");
+        let cursor = body["next_cursor"].as_str().unwrap().to_string();
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "aaaaaa" }))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/strings?sort_by=length&order=desc&limit=2&cursor={}", cursor))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"][0]["value"], "aaa");
+        assert_eq!(body["data"][1]["value"], "aa");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/strings?sort_by=length&limit=2&cursor={}", cursor))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_filter_expression() {
+        let app = test_app!();
+
+        for value in ["racecar", "hello world", "abc"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?filter=is_palindrome%20OR%20word_count%20%3D%202")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["filters_applied"]["filter"], "(is_palindrome = true OR word_count = 2)");
+
+        let req = test::TestRequest::get()
+            .uri("/strings?filter=length%20%3E%3E%203")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/filter-syntax");
+        assert_eq!(body["column"], 9);
+    }
+
+    #[actix_web::test]
+    async fn test_structured_text_and_range_filters() {
+        let app = test_app!();
+
+        for value in ["hello world", "help me now", "yellow", "shell"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?starts_with=hel&min_word_count=2&max_word_count=2")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["data"][0]["value"], "hello world");
+        assert_eq!(body["filters_applied"]["starts_with"], "hel");
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains=ell&ends_with=l")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"][0]["value"], "shell");
+
+        let req = test::TestRequest::get()
+            .uri("/strings?matches=%5Ey.*w%24&max_unique_characters=5&created_after=2000-01-01T00:00:00Z")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["filters_applied"]["matches"], "^y.*w$");
+
+        let req = test::TestRequest::get()
+            .uri("/strings?min_unique_characters=5&max_unique_characters=2")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::get()
+            .uri("/strings?matches=(a")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_contains_characters_filters() {
+        let app = test_app!();
+
+        for value in ["Café", "abc", "xyz", "👍 ok", "cafe\u{301} 👨\u{200d}👩\u{200d}👧"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_character=%C3%A9")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"][0]["value"], "Café");
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_character=%F0%9F%91%8D")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"][0]["value"], "👍 ok");
+
+        // Graphemes of several code points: a combining accent and a ZWJ sequence.
+        for grapheme in ["e%CC%81", "%F0%9F%91%A8%E2%80%8D%F0%9F%91%A9%E2%80%8D%F0%9F%91%A7"] {
+            let req = test::TestRequest::get()
+                .uri(&format!("/strings?contains_character={}", grapheme))
+                .to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["count"], 1);
+            assert_eq!(body["data"][0]["value"], "cafe\u{301} 👨\u{200d}👩\u{200d}👧");
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_character=ab")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_characters=a,z&contains_mode=any")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 4);
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_characters=c,a&case_insensitive=true")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 3);
+        assert_eq!(body["filters_applied"]["contains_mode"], "all");
+
+        // A thumbs up with a skin tone and a combining accent are one grapheme each.
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_characters=%F0%9F%91%8D%F0%9F%8F%BD,e%CC%81&contains_mode=any")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["data"][0]["value"], "cafe\u{301} 👨\u{200d}👩\u{200d}👧");
+
+        for (query, count) in [
+            ("starts_with=caf%C3%89&case_insensitive=true", 1),
+            ("ends_with=OK&case_insensitive=true", 1),
+            ("ends_with=OK", 0),
+            ("matches=%5ECAF&case_insensitive=true", 2),
+        ] {
+            let req = test::TestRequest::get()
+                .uri(&format!("/strings?{}", query))
+                .to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["count"], count, "{}", query);
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?contains_characters=ab,c")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_create_with_palindrome_policy() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "A man, a plan, a canal: Panama" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["properties"]["is_palindrome"], false);
+        assert_eq!(body["properties"]["palindrome_policy"]["ignore_punctuation"], false);
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({
+                "value": "Was it a car or a cat I saw?",
+                "palindrome_policy": { "ignore_punctuation": true }
+            }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["properties"]["is_palindrome"], true);
+        assert_eq!(body["properties"]["palindrome_policy"]["ignore_punctuation"], true);
+        assert_eq!(body["properties"]["palindrome_policy"]["case_mode"], "lowercase");
+    }
+
+    #[actix_web::test]
+    async fn test_list_analyzers() {
+        let app = test_app!();
+
+        let req = test::TestRequest::get()
+            .uri("/analyzers")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["analyzers"][0]["name"], "core");
+        assert_eq!(body["analyzers"][1]["name"], "entropy");
+        assert_eq!(body["analyzers"][1]["version"], "1.0.0");
+        assert_eq!(body["count"], 3);
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "abc" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert!(body["properties"]["entropy"].is_number());
+    }
+
+    #[actix_web::test]
+    async fn test_requested_hashes_and_digest_lookup() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings?hashes=blake3,crc32")
+            .set_json(json!({ "value": "abc" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["properties"]["hashes"]["crc32"], "352441c2");
+        assert!(body["properties"]["hashes"]["sha256"].is_string());
+        assert!(body["properties"]["hashes"].get("sha1").is_none());
+
+        let req = test::TestRequest::get()
+            .uri("/strings/abc?hashes=sha1")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["properties"]["hashes"]["sha1"], "a9993e364706816aba3e25717850c26c9cd0d89d");
+
+        let req = test::TestRequest::get()
+            .uri("/strings/by-hash/352441c2")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["value"], "abc");
+
+        let req = test::TestRequest::post()
+            .uri("/strings?hashes=md5")
+            .set_json(json!({ "value": "xyz" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_analyze_does_not_store() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/analyze?hashes=crc32")
+            .set_json(json!({ "value": "Abba", "palindrome_policy": { "case_mode": "sensitive" } }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["value"], "Abba");
+        assert_eq!(body["properties"]["is_palindrome"], false);
+        assert!(body["properties"]["hashes"]["crc32"].is_string());
+        assert!(body["properties"]["entropy"].is_number());
+
+        let req = test::TestRequest::post()
+            .uri("/analyze")
+            .set_json(json!([{ "value": "racecar" }, { "value": "hello world" }]))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["data"][0]["properties"]["is_palindrome"], true);
+        assert_eq!(body["data"][1]["properties"]["word_count"], 2);
+
+        let req = test::TestRequest::post()
+            .uri("/analyze")
+            .set_json(json!([{ "value": "ok" }, { "value": "" }]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::get().uri("/strings").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 0);
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "racecar" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    #[actix_web::test]
+    async fn test_batch_create_reports_each_item() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "existing" }))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri("/strings/batch")
+            .set_json(json!([
+                { "value": "racecar" },
+                { "value": "existing" },
+                { "value": "" },
+                { "nope": 1 },
+                { "value": "racecar" }
+            ]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let statuses: Vec<&str> = body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, ["created", "conflict", "invalid", "invalid", "conflict"]);
+        assert_eq!(body["created"], 1);
+        assert_eq!(body["conflicts"], 2);
+        assert_eq!(body["invalid"], 2);
+        assert!(body["results"][0]["id"].is_string());
+
+        let req = test::TestRequest::post()
+            .uri("/strings/batch")
+            .insert_header(("content-type", "application/x-ndjson"))
+            .set_payload("{\"value\": \"one\"}\n\n{\"value\": \"two\"}\n")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["created"], 2);
+
+        let req = test::TestRequest::get().uri("/strings").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 4);
+    }
+
+    #[actix_web::test]
+    async fn test_atomic_batch_inserts_all_or_nothing() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings/batch?atomic=true")
+            .set_json(json!([{ "value": "alpha" }, { "value": "" }]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["results"][0]["status"], "skipped");
+        assert_eq!(body["created"], 0);
+
+        let req = test::TestRequest::get().uri("/strings").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 0);
+
+        let req = test::TestRequest::post()
+            .uri("/strings/batch?atomic=true")
+            .set_json(json!([{ "value": "alpha" }, { "value": "beta" }]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::post()
+            .uri("/strings/batch?atomic=true")
+            .set_json(json!([{ "value": "gamma" }, { "value": "beta" }]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let req = test::TestRequest::get().uri("/strings").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+    }
+
+    #[actix_web::test]
+    async fn test_value_named_import_is_readable_and_deletable() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "import" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::get().uri("/strings/import").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["value"], "import");
+
+        let req = test::TestRequest::delete().uri("/strings/import").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = test::TestRequest::get().uri("/strings/import").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_value_named_batch_is_readable_and_deletable() {
+        let app = test_app!();
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .set_json(json!({ "value": "batch" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::get().uri("/strings/batch").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["value"], "batch");
+
+        let req = test::TestRequest::delete().uri("/strings/batch").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = test::TestRequest::get().uri("/strings/batch").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_export_and_import_round_trip() {
+        let source = test_app!();
+        for value in ["racecar", "hello world", "level"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&source, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings/export?format=ndjson&is_palindrome=true")
+            .to_request();
+        let resp = test::call_service(&source, req).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/x-ndjson");
+        let ndjson = test::read_body(resp).await;
+        assert_eq!(ndjson.split(|b| *b == b'\n').filter(|l| !l.is_empty()).count(), 2);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/export?format=csv")
+            .to_request();
+        let csv = test::call_and_read_body(&source, req).await;
+        let csv = String::from_utf8(csv.to_vec()).unwrap();
+        assert!(csv.starts_with("id,value,created_at,length,"));
+        assert_eq!(csv.lines().count(), 4);
+
+        let target = test_app!();
+        let req = test::TestRequest::post()
+            .uri("/strings/import?dry_run=true")
+            .set_payload(ndjson.clone())
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["imported"], 2);
+        assert_eq!(body["results"][0]["status"], "valid");
+
+        let req = test::TestRequest::post()
+            .uri("/strings/import?format=csv&mode=trust")
+            .set_payload(csv)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["imported"], 3);
+
+        let req = test::TestRequest::post()
+            .uri("/strings/import?dry_run=true")
+            .set_payload(ndjson.clone())
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["imported"], 0);
+        assert_eq!(body["conflicts"], 2);
+
+        let req = test::TestRequest::post()
+            .uri("/strings/import?mode=trust")
+            .set_payload("{\"value\": \"new\"}\n")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["invalid"], 1);
+
+        let first_line = ndjson.split(|b| *b == b'\n').next().unwrap();
+        let mut forged: serde_json::Value = serde_json::from_slice(first_line).unwrap();
+        forged["id"] = json!("not-the-digest-of-the-value");
+        let req = test::TestRequest::post()
+            .uri("/strings/import?mode=trust")
+            .set_payload(forged.to_string())
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["invalid"], 1);
+        assert_eq!(body["results"][0]["reason"], "id does not match value");
+
+        let mut hijack: serde_json::Value = serde_json::from_slice(first_line).unwrap();
+        let victim = hijack["value"].as_str().unwrap().to_string();
+        hijack["value"] = json!("attacker");
+        hijack["id"] = json!(compute_sha256("attacker"));
+        hijack["properties"]["sha256_hash"] = json!(compute_sha256("attacker"));
+        hijack["properties"]["hashes"] = json!({"sha1": compute_sha256(&victim)});
+        let req = test::TestRequest::post()
+            .uri("/strings/import?mode=trust")
+            .set_payload(hijack.to_string())
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["invalid"], 1);
+        assert_eq!(body["results"][0]["reason"], "hashes.sha1 does not match value");
+
+        let req = test::TestRequest::get().uri("/strings/level").to_request();
+        let resp = test::call_service(&target, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/export?format=columnar")
+            .to_request();
+        let resp = test::call_service(&source, req).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/json");
+        let columnar = test::read_body(resp).await;
+        let columns: serde_json::Value = serde_json::from_slice(&columnar).unwrap();
+        assert_eq!(columns["value"].as_array().unwrap().len(), 3);
+
+        let req = test::TestRequest::post()
+            .uri("/strings/import?format=columnar")
+            .set_payload(columnar)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&target, req).await;
+        assert_eq!(body["imported"], 0);
+        assert_eq!(body["conflicts"], 3);
+    }
+
+    #[actix_web::test]
+    async fn test_listing_streams_ndjson_with_summary() {
+        let app = test_app!();
+        for value in ["racecar", "hello", "level"] {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .set_json(json!({ "value": value }))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/strings?is_palindrome=true&limit=1")
+            .insert_header(("accept", "application/x-ndjson"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/x-ndjson");
+        let body = test::read_body(resp).await;
+        let lines: Vec<serde_json::Value> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["properties"]["is_palindrome"].as_bool().unwrap());
+        assert_eq!(lines[1]["summary"]["count"], 1);
+        assert_eq!(lines[1]["summary"]["filters_applied"]["is_palindrome"], true);
+        assert!(lines[1]["summary"]["next_cursor"].is_string());
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=all%20palindromic%20strings")
+            .insert_header(("accept", "text/html, application/x-ndjson;q=0.9"))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        let text = std::str::from_utf8(&body).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        let summary: serde_json::Value = serde_json::from_str(text.lines().last().unwrap()).unwrap();
+        assert_eq!(summary["summary"]["count"], 2);
+        assert_eq!(summary["summary"]["original"], "all palindromic strings");
+
+        let req = test::TestRequest::get().uri("/strings").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 3);
+    }
+
+    #[actix_web::test]
+    async fn test_negotiates_response_formats() {
+        let app = test_app!();
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .insert_header(("accept", "application/msgpack"))
+            .set_json(json!({ "value": "abba" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/msgpack");
+        let body: serde_json::Value = rmp_serde::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(body["value"], "abba");
+        assert_eq!(body["properties"]["is_palindrome"], true);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/abba")
+            .insert_header(("accept", "application/cbor"))
+            .to_request();
+        let bytes = test::call_and_read_body(&app, req).await;
+        let body: serde_json::Value = ciborium::from_reader(&bytes[..]).unwrap();
+        assert_eq!(body["properties"]["length"], 4);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/abba")
+            .insert_header(("accept", "text/html;q=0.9, application/yaml;q=0.8, application/json;q=0.5"))
+            .to_request();
+        let bytes = test::call_and_read_body(&app, req).await;
+        let body: serde_json::Value = serde_yaml::from_slice(&bytes).unwrap();
+        assert_eq!(body["value"], "abba");
+
+        let req = test::TestRequest::get()
+            .uri("/strings")
+            .insert_header(("accept", "text/csv"))
+            .to_request();
+        let bytes = test::call_and_read_body(&app, req).await;
+        let csv = String::from_utf8(bytes.to_vec()).unwrap();
+        let mut lines = csv.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert!(header.contains(&"properties.character_frequency_map.a"));
+        assert!(header.contains(&"properties.character_frequency_map.b"));
+        assert!(header.contains(&"value"));
+        assert_eq!(lines.count(), 1);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/abba")
+            .insert_header(("accept", "text/html"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/json");
+    }
+
+    #[actix_web::test]
+    async fn test_accepts_string_input_in_other_formats() {
+        let app = test_app!();
+        let bodies: [(&str, Vec<u8>); 4] = [
+            ("application/msgpack", rmp_serde::to_vec_named(&json!({ "value": "packed" })).unwrap()),
+            ("application/cbor", {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(&json!({ "value": "concise" }), &mut bytes).unwrap();
+                bytes
+            }),
+            ("application/yaml", b"value: yaml string\npalindrome_policy:\n  case_mode: sensitive\n".to_vec()),
+            ("text/csv", b"value\ncomma separated\n".to_vec()),
+        ];
+        for (content_type, body) in bodies {
+            let req = test::TestRequest::post()
+                .uri("/strings")
+                .insert_header(("content-type", content_type))
+                .set_payload(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CREATED, "{}", content_type);
+        }
+
+        let req = test::TestRequest::get().uri("/strings/yaml%20string").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["properties"]["palindrome_policy"]["case_mode"], "sensitive");
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .insert_header(("content-type", "application/xml"))
+            .set_payload("<value>x</value>")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let req = test::TestRequest::post()
+            .uri("/analyze")
+            .insert_header(("content-type", "text/csv"))
+            .set_payload("value\nracecar\nhello\n")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["count"], 2);
+    }
+
+    #[actix_web::test]
+    async fn test_field_projection_on_read_endpoints() {
+        let app = test_app!();
+        let req = test::TestRequest::post()
+            .uri("/strings?fields=id")
+            .set_json(json!({ "value": "noon" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body.as_object().unwrap().len(), 1);
+        assert!(body["id"].is_string());
+
+        let req = test::TestRequest::get()
+            .uri("/strings?fields=value,properties.length")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"][0], json!({ "value": "noon", "properties": { "length": 4 } }));
+        assert_eq!(body["count"], 1);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/noon?exclude=properties.character_frequency_map")
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert!(body["properties"].get("character_frequency_map").is_none());
+        assert_eq!(body["properties"]["is_palindrome"], true);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=palindromic%20strings&fields=value")
+            .insert_header(("accept", "application/x-ndjson"))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        let first: serde_json::Value =
+            serde_json::from_str(std::str::from_utf8(&body).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(first, json!({ "value": "noon" }));
+
+        let req = test::TestRequest::post()
+            .uri("/analyze?fields=value,properties.word_count")
+            .set_json(json!([{ "value": "a b" }]))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"][0], json!({ "value": "a b", "properties": { "word_count": 2 } }));
+
+        let req = test::TestRequest::get()
+            .uri("/strings?fields=secret")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_errors_are_problem_details() {
+        let app = test_app!();
+
+        let req = test::TestRequest::get()
+            .uri("/strings?min_length=abc&is_palindrome=maybe")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/invalid-params");
+        assert_eq!(body["status"], 400);
+        let names: Vec<&str> = body["invalid_params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["is_palindrome", "min_length"]);
+
+        let req = test::TestRequest::get()
+            .uri("/strings/filter-by-natural-language?query=longer%20than%2010%20and%20shorter%20than%205")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/conflicting-filters");
+
+        let req = test::TestRequest::get().uri("/strings/missing").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/not-found");
+        assert_eq!(body["detail"], "String not found");
+
+        let req = test::TestRequest::post()
+            .uri("/strings")
+            .insert_header(("content-type", "application/json"))
+            .set_payload("{")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/invalid-body");
+    }
+
+    #[actix_web::test]
+    async fn test_method_not_allowed_is_normalized() {
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonErrorHandler)
+                .service(web::resource("/only-post").route(web::post().to(HttpResponse::Ok))),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/only-post").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/method-not-allowed");
+    }
+
+    #[actix_web::test]
+    async fn test_inner_service_errors_are_normalized() {
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(|_, _| async {
+                    Err::<ServiceResponse, _>(actix_web::error::ErrorForbidden("blocked"))
+                })
+                .wrap(JsonErrorHandler)
+                .route("/guarded", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/guarded")
+            .insert_header(("x-request-id", "trace-7"))
+            .to_request();
+        let error = app.call(req).await.err().unwrap();
+        let resp = error.error_response();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "trace-7");
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["detail"], "blocked");
+        assert_eq!(body["request_id"], "trace-7");
+    }
+
+    #[actix_web::test]
+    async fn test_storage_errors_hide_their_cause() {
+        let app = test::init_service(App::new().wrap(JsonErrorHandler).route(
+            "/broken",
+            web::get().to(|| async {
+                Err::<HttpResponse, _>(AppError::Storage("no such table: strings".to_string()))
+            }),
+        ))
+        .await;
+
+        let req = test::TestRequest::get().uri("/broken").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/storage-error");
+        assert!(!body["detail"].as_str().unwrap().contains("no such table"));
+    }
+
+    #[actix_web::test]
+    async fn test_framework_errors_are_normalized() {
+        let app = test_app!();
+
+        let req = test::TestRequest::get().uri("/nowhere").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+        let request_id = resp.headers().get("x-request-id").unwrap().to_str().unwrap().to_string();
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/not-found");
+        assert_eq!(body["detail"], "No route for GET /nowhere");
+        assert_eq!(body["request_id"], request_id);
+
+
+        let req = test::TestRequest::get()
+            .uri("/strings?limit=-1&limit=x")
+            .insert_header(("x-request-id", "trace-42"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "trace-42");
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["request_id"], "trace-42");
+
+        let req = test::TestRequest::post()
+            .uri("/strings/batch")
+            .set_payload(vec![b' '; 17 * 1024 * 1024])
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["type"], "/problems/payload-too-large");
+
+        let req = test::TestRequest::get().uri("/strings").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key("x-request-id"));
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,30 @@
 #[cfg(test)]
 mod analyzer_tests;
 #[cfg(test)]
-mod integration_tests;
\ No newline at end of file
+mod integration_tests;
+#[cfg(test)]
+mod storage_tests;
+#[cfg(test)]
+mod filter_expr_tests;
+#[cfg(test)]
+mod nlp_parser_tests;
+#[cfg(test)]
+mod transfer_tests;
+#[cfg(test)]
+mod projection_tests;
+
+#[cfg(test)]
+use crate::models::string::StoredString;
+
+/// A string analyzed with the default settings and keyed by its SHA-256, as
+/// `POST /strings` would store it.
+#[cfg(test)]
+pub(crate) fn stored(value: &str) -> StoredString {
+    let properties = crate::services::analyzer::analyze_string(value);
+    StoredString {
+        id: properties.sha256_hash.clone(),
+        value: value.to_string(),
+        properties,
+        created_at: chrono::Utc::now(),
+    }
+}
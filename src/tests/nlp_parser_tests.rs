@@ -0,0 +1,354 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::AppError;
+    use crate::models::string::{ContainsMode, SortKey, SortOrder};
+    use crate::services::nlp_parser::{
+        interpret_natural_language, interpret_natural_language_in, parse_natural_language, Lexicon, Lexicons,
+    };
+
+    #[test]
+    fn test_single_word_palindromes() {
+        let (filters, _) = parse_natural_language("all single word palindromic strings").unwrap();
+        assert_eq!(filters.word_count, Some(1));
+        assert_eq!(filters.is_palindrome, Some(true));
+    }
+
+    #[test]
+    fn test_containing_non_ascii_letter() {
+        let (filters, _) = parse_natural_language("strings containing the letter é").unwrap();
+        assert_eq!(filters.contains_character.as_deref(), Some("é"));
+    }
+
+    #[test]
+    fn test_multiple_letters_all() {
+        let (filters, _) = parse_natural_language("strings containing the letters a, b and c").unwrap();
+        assert_eq!(filters.contains_characters, Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+        assert_eq!(filters.contains_mode, Some(ContainsMode::All));
+        assert_eq!(filters.contains_character, None);
+    }
+
+    #[test]
+    fn test_multiple_letters_any_case_insensitive() {
+        let (filters, _) = parse_natural_language("strings with the letters x or z, ignoring case").unwrap();
+        assert_eq!(filters.contains_characters, Some(vec!["x".to_string(), "z".to_string()]));
+        assert_eq!(filters.contains_mode, Some(ContainsMode::Any));
+        assert_eq!(filters.case_insensitive, Some(true));
+    }
+
+    #[test]
+    fn test_unparseable_query() {
+        assert!(parse_natural_language("something unrelated").is_err());
+    }
+
+    #[test]
+    fn test_negated_palindromes() {
+        for query in ["strings that are not palindromes", "non-palindromic strings", "strings that aren't palindromic"] {
+            let (filters, _) = parse_natural_language(query).unwrap();
+            assert_eq!(filters.is_palindrome, Some(false), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_ranges_and_comparatives() {
+        let (filters, _) = parse_natural_language("strings between 5 and 10 characters").unwrap();
+        assert_eq!((filters.min_length, filters.max_length), (Some(5), Some(10)));
+
+        let (filters, _) = parse_natural_language("at least 3 words").unwrap();
+        assert_eq!(filters.min_word_count, Some(3));
+        assert_eq!(filters.word_count, None);
+
+        let (filters, _) = parse_natural_language("no more than 4 unique characters").unwrap();
+        assert_eq!(filters.max_unique_characters, Some(4));
+
+        let (filters, _) = parse_natural_language("2 or more words, fewer than 20 letters").unwrap();
+        assert_eq!(filters.min_word_count, Some(2));
+        assert_eq!(filters.max_length, Some(19));
+    }
+
+    #[test]
+    fn test_number_words() {
+        let (filters, _) = parse_natural_language("longer than five").unwrap();
+        assert_eq!(filters.min_length, Some(6));
+
+        let (filters, _) = parse_natural_language("exactly twenty one characters").unwrap();
+        assert_eq!((filters.min_length, filters.max_length), (Some(21), Some(21)));
+
+        let (filters, _) = parse_natural_language("two words").unwrap();
+        assert_eq!(filters.word_count, Some(2));
+    }
+
+    #[test]
+    fn test_negation_without_flat_field_becomes_expression() {
+        let (filters, _) = parse_natural_language("palindromes without the letter e").unwrap();
+        assert_eq!(filters.is_palindrome, Some(true));
+        assert_eq!(filters.filter.unwrap().to_string(), "NOT value CONTAINS 'e'");
+
+        let (filters, _) = parse_natural_language("strings not between 3 and 5 characters").unwrap();
+        assert_eq!(filters.filter.unwrap().to_string(), "NOT (length >= 3 AND length <= 5)");
+    }
+
+    #[test]
+    fn test_disjunction_becomes_expression() {
+        let (filters, _) = parse_natural_language("palindromes or strings longer than 10").unwrap();
+        assert_eq!(filters.is_palindrome, None);
+        assert_eq!(filters.filter.unwrap().to_string(), "(is_palindrome = true OR length >= 11)");
+    }
+
+    #[test]
+    fn test_starts_and_ends_with() {
+        let (filters, _) = parse_natural_language("words starting with 'Ab' and ending with the letter z").unwrap();
+        assert_eq!(filters.starts_with.as_deref(), Some("Ab"));
+        assert_eq!(filters.ends_with.as_deref(), Some("z"));
+    }
+
+    #[test]
+    fn test_ordinal_vowels() {
+        for (query, letter) in [
+            ("strings containing the first vowel", "a"),
+            ("strings with the second vowel", "e"),
+        ] {
+            let interpreted = interpret_natural_language(query).unwrap();
+            assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some(letter), "{}", query);
+            assert_eq!(interpreted.confidence, 1.0, "{}", query);
+        }
+
+        let interpreted = interpret_natural_language("palindromes containing the last vowel").unwrap();
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("u"));
+        assert!(interpreted.confidence < 1.0);
+        assert_eq!(interpreted.alternatives.len(), 1);
+        let alternative = &interpreted.alternatives[0];
+        assert!(alternative.confidence < interpreted.confidence);
+        assert_eq!(alternative.parsed_filters.contains_character, None);
+        let expression = alternative.parsed_filters.filter.as_ref().unwrap().to_string();
+        assert_eq!(expression, "last_character IS vowel");
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let expression = |query: &str| {
+            let (filters, _) = parse_natural_language(query).unwrap();
+            filters.filter.map(|f| f.to_string()).unwrap_or_default()
+        };
+        assert_eq!(expression("strings containing any vowel"), "vowel_count >= 1");
+        assert_eq!(expression("no digits"), "digit_count = 0");
+        assert_eq!(expression("strings without punctuation"), "punctuation_count = 0");
+        assert_eq!(expression("at least 3 vowels"), "vowel_count >= 3");
+        assert_eq!(
+            expression("strings made of only consonants"),
+            "((consonant_count >= 1 AND vowel_count = 0) AND digit_count = 0)"
+        );
+        assert_eq!(expression("strings that start with a vowel"), "first_character IS vowel");
+        assert_eq!(
+            expression("ending with a digit or starting with a consonant"),
+            "(last_character IS digit OR first_character IS consonant)"
+        );
+        assert_eq!(expression("words that don't end with punctuation"), "NOT last_character IS punctuation");
+
+        let interpreted = interpret_natural_language("strings that start with a vowel").unwrap();
+        assert!(interpreted.alternatives.is_empty());
+
+        assert!(matches!(
+            parse_natural_language("only vowels with at least 2 consonants"),
+            Err(AppError::ConflictingFilters(_))
+        ));
+    }
+
+    #[test]
+    fn test_affix_words_keep_their_case() {
+        let (filters, _) = parse_natural_language("strings starting with Foo").unwrap();
+        assert_eq!(filters.starts_with.as_deref(), Some("Foo"));
+        assert_eq!(filters.case_insensitive, None);
+
+        let (filters, _) = parse_natural_language("strings ending with Bar, ignoring case").unwrap();
+        assert_eq!(filters.ends_with.as_deref(), Some("Bar"));
+        assert_eq!(filters.case_insensitive, Some(true));
+    }
+
+    #[test]
+    fn test_ends_with_the_letter() {
+        let (filters, _) = parse_natural_language("strings that end with the letter z").unwrap();
+        assert_eq!(filters.ends_with.as_deref(), Some("z"));
+        assert_eq!(filters.filter, None);
+    }
+
+    #[test]
+    fn test_sort_and_limit() {
+        let interpreted = interpret_natural_language("the 5 longest palindromes").unwrap();
+        assert_eq!(interpreted.parsed_filters.is_palindrome, Some(true));
+        assert_eq!(interpreted.sort_by, Some(SortKey::Length));
+        assert_eq!(interpreted.order, Some(SortOrder::Desc));
+        assert_eq!(interpreted.limit, Some(5));
+        assert_eq!(interpreted.clauses[0].text, "5 longest");
+        assert_eq!(interpreted.clauses[0].filter, "sort_by=length&order=desc&limit=5");
+        assert_eq!(interpreted.confidence, 1.0);
+
+        let interpreted = interpret_natural_language("shortest strings containing q").unwrap();
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("q"));
+        assert_eq!((interpreted.sort_by, interpreted.order), (Some(SortKey::Length), Some(SortOrder::Asc)));
+        assert_eq!(interpreted.limit, None);
+
+        let interpreted = interpret_natural_language("top 3 strings with the most words").unwrap();
+        assert_eq!((interpreted.sort_by, interpreted.order), (Some(SortKey::WordCount), Some(SortOrder::Desc)));
+        assert_eq!(interpreted.limit, Some(3));
+
+        let interpreted = interpret_natural_language("palindromes sorted by unique characters descending").unwrap();
+        assert_eq!(interpreted.sort_by, Some(SortKey::UniqueCharacters));
+        assert_eq!(interpreted.order, Some(SortOrder::Desc));
+    }
+
+    #[test]
+    fn test_sort_alone_matches_everything() {
+        let interpreted = interpret_natural_language("the 10 newest strings").unwrap();
+        assert_eq!((interpreted.sort_by, interpreted.limit), (Some(SortKey::CreatedAt), Some(10)));
+        assert_eq!(interpreted.parsed_filters.filter, None);
+
+        assert!(matches!(
+            interpret_natural_language("longest palindromes sorted by word count"),
+            Err(AppError::ConflictingFilters(_))
+        ));
+        assert!(matches!(interpret_natural_language("top 5000 palindromes"), Err(AppError::InvalidParams(_))));
+    }
+
+    #[test]
+    fn test_with_does_not_read_article_as_letter() {
+        let (filters, _) = parse_natural_language("strings with a single word").unwrap();
+        assert_eq!(filters.word_count, Some(1));
+        assert_eq!(filters.contains_character, None);
+    }
+
+    #[test]
+    fn test_conflicting_clauses() {
+        assert!(matches!(
+            parse_natural_language("longer than 10 and shorter than 5"),
+            Err(AppError::ConflictingFilters(_))
+        ));
+        assert!(matches!(
+            parse_natural_language("palindromes that are not palindromes"),
+            Err(AppError::ConflictingFilters(_))
+        ));
+        assert!(matches!(parse_natural_language("shorter than 0"), Err(AppError::ConflictingFilters(_))));
+        assert!(matches!(parse_natural_language("ignoring case"), Err(AppError::UnrecognizedQuery(_))));
+    }
+
+    #[test]
+    fn test_clauses_report_spans_and_filters() {
+        let interpreted = interpret_natural_language("palindromes without the letter e").unwrap();
+        let clauses: Vec<(&str, &str)> = interpreted
+            .clauses
+            .iter()
+            .map(|c| (c.text.as_str(), c.filter.as_str()))
+            .collect();
+        assert_eq!(
+            clauses,
+            [
+                ("palindromes", "is_palindrome = true"),
+                ("without the letter e", "NOT value CONTAINS 'e'"),
+            ]
+        );
+        assert_eq!((interpreted.clauses[1].start, interpreted.clauses[1].end), (12, 32));
+        assert_eq!(interpreted.confidence, 1.0);
+        assert!(interpreted.alternatives.is_empty());
+    }
+
+    #[test]
+    fn test_ignored_words_lower_confidence() {
+        let interpreted = interpret_natural_language("shiny palindromes").unwrap();
+        assert_eq!(interpreted.ignored_words.len(), 1);
+        assert_eq!(interpreted.ignored_words[0].word, "shiny");
+        assert_eq!((interpreted.ignored_words[0].start, interpreted.ignored_words[0].end), (0, 5));
+        assert_eq!(interpreted.confidence, 0.5);
+    }
+
+    #[test]
+    fn test_unitless_comparison_offers_word_count_reading() {
+        let interpreted = interpret_natural_language("more than 3").unwrap();
+        assert_eq!(interpreted.parsed_filters.min_length, Some(4));
+        assert!(interpreted.confidence < 1.0);
+        assert_eq!(interpreted.alternatives.len(), 1);
+        let alternative = &interpreted.alternatives[0];
+        assert_eq!(alternative.parsed_filters.min_word_count, Some(4));
+        assert!(alternative.confidence < interpreted.confidence);
+    }
+
+    #[test]
+    fn test_article_or_letter_is_ambiguous() {
+        let interpreted = interpret_natural_language("strings containing a z").unwrap();
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("z"));
+        assert_eq!(interpreted.alternatives[0].parsed_filters.contains_character.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_or_scope_is_ambiguous() {
+        let interpreted = interpret_natural_language("palindromes longer than 5 or shorter than 2").unwrap();
+        assert_eq!(
+            interpreted.parsed_filters.filter.as_ref().unwrap().to_string(),
+            "((is_palindrome = true AND length >= 6) OR length <= 1)"
+        );
+        let alternative = &interpreted.alternatives[0].parsed_filters;
+        assert_eq!(alternative.is_palindrome, Some(true));
+        assert_eq!(alternative.filter.as_ref().unwrap().to_string(), "(length >= 6 OR length <= 1)");
+    }
+
+    #[test]
+    fn test_queries_in_other_languages() {
+        let lexicons = Lexicons::builtin();
+        let read = |lang: &str, query: &str| {
+            interpret_natural_language_in(query, lexicons.get(lang).unwrap()).unwrap()
+        };
+
+        let interpreted = read("es", "cadenas que contienen la letra y y tienen más de 5 caracteres");
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("y"));
+        assert_eq!(interpreted.parsed_filters.min_length, Some(6));
+        assert_eq!(interpreted.language, "es");
+        assert_eq!(interpreted.confidence, 1.0);
+
+        let interpreted = read("es", "cadenas que terminan en o");
+        assert_eq!(interpreted.parsed_filters.ends_with.as_deref(), Some("o"));
+
+        let interpreted = read("es", "cadenas que contienen la y");
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("y"));
+
+        let interpreted = read("es", "palindromos que contienen la o y tienen más de 5 caracteres");
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("o"));
+        assert_eq!(interpreted.parsed_filters.min_length, Some(6));
+
+        let interpreted = read("es", "palindromos de una sola palabra");
+        assert_eq!(interpreted.parsed_filters.word_count, Some(1));
+        assert_eq!(interpreted.parsed_filters.is_palindrome, Some(true));
+        assert_eq!(interpreted.clauses[1].text, "una sola palabra");
+
+        let interpreted = read("fr", "les 3 plus longues chaînes qui ne contiennent pas la lettre e");
+        assert_eq!(interpreted.parsed_filters.filter.unwrap().to_string(), "NOT value CONTAINS 'e'");
+        assert_eq!((interpreted.sort_by, interpreted.limit), (Some(SortKey::Length), Some(3)));
+
+        let interpreted = read("de", "Palindrome, die länger als 5 Zeichen sind und den Buchstaben a enthalten");
+        assert_eq!(interpreted.parsed_filters.is_palindrome, Some(true));
+        assert_eq!(interpreted.parsed_filters.min_length, Some(6));
+        assert_eq!(interpreted.parsed_filters.contains_character.as_deref(), Some("a"));
+        assert!(interpreted.ignored_words.is_empty());
+
+        let interpreted = read("de-AT", "Zeichenketten ohne Ziffern");
+        assert_eq!(interpreted.parsed_filters.filter.unwrap().to_string(), "digit_count = 0");
+    }
+
+    #[test]
+    fn test_locales_from_data_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("it.json"),
+            r#"{"locale": "it", "phrases": {"palindromi": "palindromes", "più lunghe di": "longer than"}}"#,
+        )
+        .unwrap();
+        let mut lexicons = Lexicons::builtin();
+        lexicons.load_dir(dir.path()).unwrap();
+
+        let interpreted = interpret_natural_language_in("palindromi più lunghe di 4", lexicons.get("it").unwrap()).unwrap();
+        assert_eq!(interpreted.parsed_filters.is_palindrome, Some(true));
+        assert_eq!(interpreted.parsed_filters.min_length, Some(5));
+
+        assert_eq!(lexicons.negotiate("it-CH;q=0.8, fr;q=0.9").locale(), "fr");
+        assert_eq!(lexicons.negotiate("pt-BR, es;q=0.5").locale(), "es");
+        assert_eq!(lexicons.negotiate("pt-BR").locale(), "en");
+        assert!(Lexicon::from_json(r#"{"locale": "xx", "phrases": {"'quoted'": "palindromes"}}"#).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::string::PalindromePolicy;
+    use crate::services::analyzer::AnalysisSettings;
+    use crate::utils::projection::{parse_projection, ProjectionQuery};
+    use serde_json::json;
+
+    fn projection(fields: Option<&str>, exclude: Option<&str>) -> crate::utils::projection::Projection {
+        parse_projection(&ProjectionQuery {
+            fields: fields.map(str::to_string),
+            exclude: exclude.map(str::to_string),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fields_keep_only_requested_paths() {
+        let value = json!({
+            "id": "abc",
+            "value": "aa",
+            "properties": { "length": 2, "word_count": 1, "character_frequency_map": { "a": 2 } },
+            "created_at": "2024-01-01T00:00:00Z"
+        });
+        let projected = projection(Some("id, properties.length"), None).apply(value.clone());
+        assert_eq!(projected, json!({ "id": "abc", "properties": { "length": 2 } }));
+
+        let projected = projection(None, Some("properties.character_frequency_map,created_at")).apply(value);
+        assert_eq!(projected["properties"], json!({ "length": 2, "word_count": 1 }));
+        assert!(projected.get("created_at").is_none());
+    }
+
+    #[test]
+    fn test_listing_projects_each_entry() {
+        let listing = json!({ "data": [{ "id": "1", "value": "a" }, { "id": "2", "value": "b" }], "count": 2 });
+        let projected = projection(Some("value"), None).apply(listing);
+        assert_eq!(projected, json!({ "data": [{ "value": "a" }, { "value": "b" }], "count": 2 }));
+    }
+
+    #[test]
+    fn test_wants_and_unknown_fields() {
+        let p = projection(Some("properties.length"), Some("properties.length.x"));
+        assert!(p.wants(&["properties"]));
+        assert!(p.wants(&["properties", "length"]));
+        assert!(!p.wants(&["properties", "entropy"]));
+        assert!(!p.wants(&["value"]));
+
+        let p = projection(None, Some("properties"));
+        assert!(!p.wants(&["properties", "length"]));
+        assert!(p.wants(&["id"]));
+
+        assert!(parse_projection(&ProjectionQuery { fields: Some("nope".to_string()), exclude: None }).is_err());
+        assert!(parse_projection(&ProjectionQuery { fields: Some("properties.".to_string()), exclude: None }).is_err());
+    }
+
+    #[test]
+    fn test_unrequested_properties_are_not_computed() {
+        let settings = AnalysisSettings::default();
+        let p = projection(Some("properties.length"), None);
+        let result = settings.analyze_projected("hello", &PalindromePolicy::default(), &[], &p);
+        assert_eq!(result.length, 5);
+        assert!(result.character_frequency_map.is_empty());
+        assert!(result.sha256_hash.is_empty());
+        assert!(result.extra.is_empty());
+        assert!(result.hashes.is_empty());
+    }
+}
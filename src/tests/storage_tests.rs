@@ -0,0 +1,528 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::string::{ContainsMode, Filters, SortKey, SortOrder, StoredString};
+    use crate::services::hashing::HashAlgorithm;
+    use crate::services::storage::indexes::IndexedStrings;
+    use crate::services::storage::{FileStore, InMemoryStore, PagedRows, SharedStore, SqliteStore, StringStore};
+    use crate::tests::stored;
+    use crate::utils::filters::Pattern;
+    use crate::utils::pagination::{Cursor, Pagination};
+    use actix_web::web;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_save_and_get_by_value() {
+        let store = InMemoryStore::new();
+        store.save_string(stored("hello")).unwrap();
+        assert_eq!(store.get_string_by_value("hello").unwrap().unwrap().value, "hello");
+    }
+
+    /// A string whose id comes from a non-default algorithm, with extra digests.
+    fn stored_with_hashes(value: &str) -> StoredString {
+        let mut string = stored(value);
+        string.id = HashAlgorithm::Blake3.digest(value);
+        for algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Crc32] {
+            string.properties.hashes.insert(algorithm, algorithm.digest(value));
+        }
+        string
+    }
+
+    #[test]
+    fn test_lookup_by_any_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores: Vec<Box<dyn StringStore>> = vec![
+            Box::new(InMemoryStore::new()),
+            Box::new(FileStore::open(dir.path(), 0).unwrap()),
+            Box::new(SqliteStore::open_in_memory().unwrap()),
+        ];
+        for store in stores {
+            store.save_string(stored_with_hashes("hello")).unwrap();
+            store.save_string(stored("world")).unwrap();
+
+            for digest in [
+                HashAlgorithm::Blake3.digest("hello"),
+                HashAlgorithm::Crc32.digest("hello"),
+                HashAlgorithm::Sha256.digest("hello").to_uppercase(),
+            ] {
+                assert_eq!(store.get_string_by_digest(&digest).unwrap().unwrap().value, "hello");
+            }
+            assert!(store.get_string_by_digest(&HashAlgorithm::Crc32.digest("world")).unwrap().is_none());
+            assert_eq!(store.get_string_by_value("hello").unwrap().unwrap().value, "hello");
+
+            store.delete_string("hello").unwrap();
+            assert!(store.get_string_by_digest(&HashAlgorithm::Crc32.digest("hello")).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_save_all_is_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores: Vec<Box<dyn StringStore>> = vec![
+            Box::new(InMemoryStore::new()),
+            Box::new(FileStore::open(dir.path(), 0).unwrap()),
+            Box::new(SqliteStore::open_in_memory().unwrap()),
+        ];
+        for store in stores {
+            store.save_string(stored("taken")).unwrap();
+            assert!(store.save_all(vec![stored("fresh"), stored("taken")]).is_err());
+            assert!(store.save_all(vec![stored("dup"), stored("dup")]).is_err());
+            assert!(store.get_string_by_value("fresh").unwrap().is_none());
+            assert!(store.get_string_by_value("dup").unwrap().is_none());
+
+            store.save_all(vec![stored("fresh"), stored("other")]).unwrap();
+            assert_eq!(store.get_all_strings(&Filters::default()).unwrap().len(), 3);
+        }
+
+        let reopened = FileStore::open(dir.path(), 0).unwrap();
+        assert!(reopened.get_string_by_value("other").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_pages_follow_the_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores: Vec<Box<dyn StringStore>> = vec![
+            Box::new(InMemoryStore::new()),
+            Box::new(FileStore::open(dir.path(), 0).unwrap()),
+            Box::new(SqliteStore::open_in_memory().unwrap()),
+        ];
+        for store in stores {
+            for value in ["a", "bb", "ccc", "dddd", "eeeee", "xyz", "xy"] {
+                store.save_string(stored(value)).unwrap();
+            }
+            // `matches` is never pushed down, so SQLite has to keep reading
+            // past rows the residual check drops.
+            let filters = Filters {
+                matches: Some(Pattern::new("^[a-e]+$").unwrap()),
+                ..Filters::default()
+            };
+            let mut pagination = Pagination {
+                sort_by: SortKey::Length,
+                order: SortOrder::Desc,
+                limit: Some(2),
+                cursor: None,
+            };
+            let mut values = Vec::new();
+            loop {
+                let (page, next_cursor) = store.get_page(&filters, &pagination).unwrap();
+                values.extend(page.into_iter().map(|s| s.value));
+                if next_cursor.is_none() {
+                    break;
+                }
+                pagination.cursor = next_cursor;
+            }
+            assert_eq!(values, ["eeeee", "dddd", "ccc", "bb", "a"]);
+        }
+    }
+
+    #[test]
+    fn test_paged_rows_walk_several_chunks() {
+        let store: Arc<dyn StringStore> = Arc::new(SqliteStore::open_in_memory().unwrap());
+        let store: SharedStore = web::Data::from(store);
+        store.save_all((0..250).map(|i| stored(&format!("value {}", i))).collect()).unwrap();
+
+        let pagination = Pagination {
+            sort_by: SortKey::UniqueCharacters,
+            limit: Some(230),
+            ..Pagination::default()
+        };
+        let mut rows = PagedRows::new(store.clone(), Filters::default(), pagination.clone());
+        let walked: Vec<StoredString> = rows.by_ref().map(Result::unwrap).collect();
+        let (page, next_cursor) = store.get_page(&Filters::default(), &pagination).unwrap();
+        assert_eq!(walked.len(), 230);
+        assert!(walked.iter().map(|s| &s.id).eq(page.iter().map(|s| &s.id)));
+        assert_eq!(rows.next_cursor().map(Cursor::encode), next_cursor.as_ref().map(Cursor::encode));
+
+        let mut rows = PagedRows::new(store, Filters::default(), Pagination::default());
+        assert_eq!(rows.by_ref().count(), 250);
+        assert!(rows.next_cursor().is_none());
+    }
+
+    #[test]
+    fn test_save_duplicate_rejected() {
+        let store = InMemoryStore::new();
+        store.save_string(stored("hello")).unwrap();
+        assert!(store.save_string(stored("hello")).is_err());
+    }
+
+    #[test]
+    fn test_delete_removes_string() {
+        let store = InMemoryStore::new();
+        let s = stored("hello");
+        let id = s.id.clone();
+        store.save_string(s).unwrap();
+        store.delete_string("hello").unwrap();
+        assert!(!store.string_exists(&id).unwrap());
+        assert!(store.delete_string("hello").is_err());
+    }
+
+    #[test]
+    fn test_get_all_strings_applies_filters() {
+        let store = InMemoryStore::new();
+        store.save_string(stored("racecar")).unwrap();
+        store.save_string(stored("hello world")).unwrap();
+
+        let filters = Filters {
+            is_palindrome: Some(true),
+            ..Filters::default()
+        };
+        let result = store.get_all_strings(&filters).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value, "racecar");
+    }
+
+    #[test]
+    fn test_file_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = FileStore::open(dir.path(), 1000).unwrap();
+            store.save_string(stored("hello")).unwrap();
+            store.save_string(stored("racecar")).unwrap();
+            store.delete_string("hello").unwrap();
+        }
+
+        let store = FileStore::open(dir.path(), 1000).unwrap();
+        assert!(store.get_string_by_value("hello").unwrap().is_none());
+        assert_eq!(store.get_string_by_value("racecar").unwrap().unwrap().value, "racecar");
+    }
+
+    #[test]
+    fn test_file_store_compacts_into_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = FileStore::open(dir.path(), 2).unwrap();
+            store.save_string(stored("one")).unwrap();
+            store.save_string(stored("two")).unwrap();
+            store.save_string(stored("three")).unwrap();
+        }
+
+        assert!(dir.path().join("snapshot.json").exists());
+        let journal = std::fs::read_to_string(dir.path().join("journal.ndjson")).unwrap();
+        assert_eq!(journal.lines().count(), 1);
+
+        let store = FileStore::open(dir.path(), 2).unwrap();
+        assert_eq!(store.get_all_strings(&Filters::default()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_file_store_skips_torn_last_record() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = FileStore::open(dir.path(), 1000).unwrap();
+            store.save_string(stored("hello")).unwrap();
+        }
+        let journal_path = dir.path().join("journal.ndjson");
+        let mut journal = OpenOptions::new().append(true).open(&journal_path).unwrap();
+        journal.write_all(b"{\"op\":\"save\",\"string\":{\"id\":\"ab").unwrap();
+        drop(journal);
+
+        let store = FileStore::open(dir.path(), 1000).unwrap();
+        assert!(store.get_string_by_value("hello").unwrap().is_some());
+        store.save_string(stored("world")).unwrap();
+        drop(store);
+
+        let store = FileStore::open(dir.path(), 1000).unwrap();
+        assert_eq!(store.get_all_strings(&Filters::default()).unwrap().len(), 2);
+    }
+
+    /// `value` as releases before format versioning stored it: `length` in
+    /// bytes and no code point, grapheme or character class counts.
+    fn legacy_json(value: &str) -> serde_json::Value {
+        let mut json = serde_json::to_value(stored(value)).unwrap();
+        let properties = json["properties"].as_object_mut().unwrap();
+        properties.insert("length".to_string(), value.len().into());
+        for field in ["byte_length", "code_point_count", "grapheme_count", "vowel_count", "consonant_count"] {
+            properties.remove(field);
+        }
+        json
+    }
+
+    #[test]
+    fn test_file_store_reanalyzes_legacy_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot = serde_json::json!([legacy_json("cafe\u{301}")]);
+        std::fs::write(dir.path().join("snapshot.json"), snapshot.to_string()).unwrap();
+        let record = serde_json::json!({ "op": "save", "string": legacy_json("noël") });
+        std::fs::write(dir.path().join("journal.ndjson"), format!("{}\n", record)).unwrap();
+
+        let store = FileStore::open(dir.path(), 1000).unwrap();
+        for value in ["cafe\u{301}", "noël"] {
+            let properties = store.get_string_by_value(value).unwrap().unwrap().properties;
+            assert_eq!(properties.length, 4);
+            assert_eq!(properties.grapheme_count, 4);
+            assert_eq!(properties.byte_length, value.len());
+            assert_eq!(properties.vowel_count, 2);
+            assert_eq!(properties.consonant_count, 2);
+        }
+
+        store.compact().unwrap();
+        drop(store);
+        let store = FileStore::open(dir.path(), 1000).unwrap();
+        assert_eq!(store.get_string_by_value("noël").unwrap().unwrap().properties.code_point_count, 4);
+    }
+
+    #[test]
+    fn test_sqlite_store_round_trip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let s = stored("hello world");
+        let id = s.id.clone();
+        store.save_string(s).unwrap();
+        assert!(store.save_string(stored("hello world")).is_err());
+
+        let fetched = store.get_string_by_hash(&id).unwrap().unwrap();
+        assert_eq!(fetched.value, "hello world");
+        assert_eq!(fetched.properties.word_count, 2);
+        assert_eq!(fetched.properties.character_frequency_map.get("l"), Some(&3));
+
+        store.delete_string("hello world").unwrap();
+        assert!(!store.string_exists(&id).unwrap());
+    }
+
+    #[test]
+    fn test_sqlite_store_pushes_down_filters() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        store.save_string(stored("racecar")).unwrap();
+        store.save_string(stored("level")).unwrap();
+        store.save_string(stored("hello world")).unwrap();
+
+        let filters = Filters {
+            is_palindrome: Some(true),
+            min_length: Some(6),
+            contains_character: Some("r".to_string()),
+            ..Filters::default()
+        };
+        let result = store.get_all_strings(&filters).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value, "racecar");
+
+        let filters = Filters {
+            starts_with: Some("le".to_string()),
+            ends_with: Some("vel".to_string()),
+            max_unique_characters: Some(3),
+            ..Filters::default()
+        };
+        let result = store.get_all_strings(&filters).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value, "level");
+
+        let filters = Filters {
+            contains_characters: Some(vec!["R".to_string(), "w".to_string()]),
+            contains_mode: Some(ContainsMode::Any),
+            case_insensitive: Some(true),
+            ..Filters::default()
+        };
+        assert_eq!(store.get_all_strings(&filters).unwrap().len(), 2);
+        let filters = Filters {
+            starts_with: Some("HELLO".to_string()),
+            ends_with: Some("World".to_string()),
+            case_insensitive: Some(true),
+            ..Filters::default()
+        };
+        assert_eq!(store.get_all_strings(&filters).unwrap().len(), 1);
+
+        let filters = Filters {
+            max_length: Some(usize::MAX),
+            max_word_count: Some(usize::MAX),
+            ..Filters::default()
+        };
+        assert_eq!(store.get_all_strings(&filters).unwrap().len(), 3);
+        let filters = Filters {
+            min_word_count: Some(usize::MAX),
+            ..Filters::default()
+        };
+        assert!(store.get_all_strings(&filters).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sqlite_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strings.db");
+        {
+            let store = SqliteStore::open(&path).unwrap();
+            store.save_string(stored("persisted")).unwrap();
+        }
+        let store = SqliteStore::open(&path).unwrap();
+        assert!(store.get_string_by_value("persisted").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_sqlite_store_reports_unreadable_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strings.db");
+        {
+            let store = SqliteStore::open(&path).unwrap();
+            store.save_string(stored("broken")).unwrap();
+        }
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch("UPDATE strings SET properties = 'not json';")
+            .unwrap();
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert!(store.get_all_strings(&Filters::default()).is_err());
+        assert!(store.get_string_by_value("broken").is_err());
+        assert!(store.get_string_by_digest(&HashAlgorithm::Sha256.digest("broken")).is_err());
+    }
+
+    #[test]
+    fn test_indexes_intersect_filters() {
+        let mut indexed = IndexedStrings::new();
+        for value in ["racecar", "level", "noon", "hello world", "radar"] {
+            indexed.insert(stored(value)).unwrap();
+        }
+
+        let filters = Filters {
+            is_palindrome: Some(true),
+            min_length: Some(5),
+            max_length: Some(7),
+            contains_character: Some("r".to_string()),
+            ..Filters::default()
+        };
+        let mut values: Vec<String> = indexed.filter(&filters).into_iter().map(|s| s.value).collect();
+        values.sort();
+        assert_eq!(values, vec!["racecar", "radar"]);
+
+        let filters = Filters {
+            word_count: Some(2),
+            ..Filters::default()
+        };
+        assert_eq!(indexed.filter(&filters).len(), 1);
+    }
+
+    #[test]
+    fn test_indexes_stay_in_sync_on_remove() {
+        let mut indexed = IndexedStrings::new();
+        let s = stored("racecar");
+        let id = s.id.clone();
+        indexed.insert(s).unwrap();
+        indexed.insert(stored("radar")).unwrap();
+        indexed.remove(&id);
+
+        let filters = Filters {
+            contains_character: Some("c".to_string()),
+            ..Filters::default()
+        };
+        assert!(indexed.filter(&filters).is_empty());
+
+        let filters = Filters {
+            min_length: Some(7),
+            ..Filters::default()
+        };
+        assert!(indexed.filter(&filters).is_empty());
+        assert_eq!(indexed.len(), 1);
+    }
+
+    #[test]
+    fn test_indexes_reject_digest_owned_by_another_string() {
+        let mut indexed = IndexedStrings::new();
+        indexed.insert(stored_with_hashes("hello")).unwrap();
+
+        let mut clash = stored("world");
+        clash.properties.hashes.insert(HashAlgorithm::Crc32, HashAlgorithm::Crc32.digest("hello"));
+        let clash_id = clash.id.clone();
+        assert!(indexed.insert(clash).is_err());
+        assert!(indexed.remove(&clash_id).is_none());
+
+        let found = indexed.get_by_digest(&HashAlgorithm::Crc32.digest("hello")).unwrap();
+        assert_eq!(found.value, "hello");
+    }
+
+    #[test]
+    fn test_lookup_by_value_ignores_forged_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores: Vec<Box<dyn StringStore>> = vec![
+            Box::new(InMemoryStore::new()),
+            Box::new(FileStore::open(dir.path(), 0).unwrap()),
+            Box::new(SqliteStore::open_in_memory().unwrap()),
+        ];
+        for store in stores {
+            let mut forged = stored("attacker");
+            forged.properties.sha256_hash = HashAlgorithm::Sha256.digest("victim");
+            store.save_string(forged).unwrap();
+
+            assert!(store.get_string_by_value("victim").unwrap().is_none());
+            assert!(store.delete_string("victim").is_err());
+            assert!(store.string_exists(&HashAlgorithm::Sha256.digest("attacker")).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_equal_digests_of_different_algorithms_do_not_clash() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores: Vec<Box<dyn StringStore>> = vec![
+            Box::new(InMemoryStore::new()),
+            Box::new(FileStore::open(dir.path(), 0).unwrap()),
+            Box::new(SqliteStore::open_in_memory().unwrap()),
+        ];
+        for store in stores {
+            store.save_string(stored_with_hashes("hello")).unwrap();
+            let mut other = stored("world");
+            other.properties.hashes.insert(HashAlgorithm::Xxhash, HashAlgorithm::Crc32.digest("hello"));
+            store.save_string(other).unwrap();
+
+            store.delete_string("world").unwrap();
+            let found = store.get_string_by_digest(&HashAlgorithm::Crc32.digest("hello")).unwrap().unwrap();
+            assert_eq!(found.value, "hello");
+        }
+    }
+
+    #[test]
+    fn test_sqlite_store_migrates_legacy_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strings.db");
+        {
+            let conn = rusqlite::Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE strings (
+                    id TEXT PRIMARY KEY, value TEXT NOT NULL, length INTEGER NOT NULL,
+                    is_palindrome INTEGER NOT NULL, unique_characters INTEGER NOT NULL,
+                    word_count INTEGER NOT NULL, sha256_hash TEXT NOT NULL,
+                    character_frequency_map TEXT NOT NULL, created_at TEXT NOT NULL
+                );",
+            ).unwrap();
+            let legacy = stored("cafe\u{301}");
+            conn.execute(
+                "INSERT INTO strings VALUES (?1, ?2, 6, 0, 5, 1, ?1, '{}', ?3)",
+                rusqlite::params![legacy.id, legacy.value, legacy.created_at],
+            ).unwrap();
+        }
+
+        let store = SqliteStore::open(&path).unwrap();
+        let migrated = store.get_string_by_value("cafe\u{301}").unwrap().unwrap();
+        assert_eq!(migrated.properties.length, 4);
+        assert_eq!(migrated.properties.byte_length, 6);
+
+        let filters = Filters {
+            max_length: Some(4),
+            ..Filters::default()
+        };
+        assert_eq!(store.get_all_strings(&filters).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sqlite_store_reanalyzes_older_databases() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strings.db");
+        {
+            let store = SqliteStore::open(&path).unwrap();
+            store.save_string(stored_with_hashes("hello")).unwrap();
+        }
+        {
+            let conn = rusqlite::Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "DROP TABLE digests;
+                 UPDATE strings SET properties = json_remove(properties, '$.vowel_count');
+                 PRAGMA user_version = 1;",
+            ).unwrap();
+        }
+
+        let store = SqliteStore::open(&path).unwrap();
+        let digest = HashAlgorithm::Crc32.digest("hello");
+        let migrated = store.get_string_by_digest(&digest).unwrap().unwrap();
+        assert_eq!(migrated.value, "hello");
+        assert_eq!(migrated.properties.vowel_count, 2);
+        store.delete_string("hello").unwrap();
+        assert!(store.get_string_by_digest(&digest).unwrap().is_none());
+    }
+}
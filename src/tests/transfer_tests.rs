@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::transfer::{columnar_body, csv_header, csv_line, ndjson_line, parse_import, TransferFormat};
+    use crate::tests::stored;
+
+    #[test]
+    fn test_csv_round_trip_keeps_quoted_values() {
+        let original = stored("a, \"quoted\"\nline");
+        let mut body = csv_header().unwrap();
+        body.extend(csv_line(&original).unwrap());
+
+        let records = parse_import(&body, TransferFormat::Csv).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = records.into_iter().next().unwrap().unwrap();
+        assert_eq!(record.value, original.value);
+        assert_eq!(record.id.as_deref(), Some(original.id.as_str()));
+        assert_eq!(record.created_at, Some(original.created_at));
+        assert_eq!(record.properties.unwrap().length, original.properties.length);
+    }
+
+    #[test]
+    fn test_columnar_round_trip() {
+        let originals = [stored("racecar"), stored("hello world")];
+        let body = columnar_body(&originals).unwrap();
+        let columns: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(columns["value"], serde_json::json!(["racecar", "hello world"]));
+        assert_eq!(columns["is_palindrome"], serde_json::json!([true, false]));
+        assert_eq!(columns["word_count"], serde_json::json!([1, 2]));
+
+        let records = parse_import(&body, TransferFormat::Columnar).unwrap();
+        assert_eq!(records.len(), 2);
+        for (record, original) in records.into_iter().zip(&originals) {
+            let record = record.unwrap();
+            assert_eq!(record.value, original.value);
+            assert_eq!(record.id.as_deref(), Some(original.id.as_str()));
+            assert_eq!(record.created_at, Some(original.created_at));
+            assert_eq!(record.properties.unwrap().sha256_hash, original.properties.sha256_hash);
+        }
+
+        let records = parse_import(br#"{"value": ["bare", 7], "id": [null, null]}"#, TransferFormat::Columnar).unwrap();
+        assert!(records[0].as_ref().unwrap().id.is_none());
+        assert!(records[1].is_err());
+        assert!(parse_import(br#"{"value": ["a"], "id": []}"#, TransferFormat::Columnar).is_err());
+        assert!(parse_import(br#"{"id": ["a"]}"#, TransferFormat::Columnar).is_err());
+    }
+
+    #[test]
+    fn test_import_reports_bad_records_in_place() {
+        let mut body = ndjson_line(&stored("ok")).unwrap();
+        body.extend(b"{not json}\n\n{\"value\": \"bare\"}\n");
+
+        let records = parse_import(&body, TransferFormat::Ndjson).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records[0].is_ok());
+        assert!(records[1].is_err());
+        assert!(records[2].as_ref().unwrap().properties.is_none());
+
+        assert!(parse_import(b"id,length\n1,2\n", TransferFormat::Csv).is_err());
+        assert!(TransferFormat::parse("parquet").is_err());
+    }
+}
@@ -0,0 +1,617 @@
+use crate::models::string::{CharClass, StoredString};
+use crate::services::analyzer::char_class;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const MAX_EXPRESSION_LENGTH: usize = 2000;
+const MAX_DEPTH: usize = 64;
+
+/// Parse error for a `filter=` expression. `column` is the 1-based character
+/// position of the offending token.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FilterSyntaxError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl FilterSyntaxError {
+    fn new(message: impl Into<String>, column: usize) -> Self {
+        FilterSyntaxError {
+            message: message.into(),
+            column,
+        }
+    }
+}
+
+impl fmt::Display for FilterSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Length,
+    WordCount,
+    UniqueCharacters,
+    IsPalindrome,
+    Value,
+    VowelCount,
+    ConsonantCount,
+    DigitCount,
+    PunctuationCount,
+    /// The first grapheme cluster, tested with `IS <class>`.
+    FirstCharacter,
+    LastCharacter,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "length" => Some(Field::Length),
+            "word_count" => Some(Field::WordCount),
+            "unique_characters" => Some(Field::UniqueCharacters),
+            "is_palindrome" => Some(Field::IsPalindrome),
+            "value" => Some(Field::Value),
+            "vowel_count" => Some(Field::VowelCount),
+            "consonant_count" => Some(Field::ConsonantCount),
+            "digit_count" => Some(Field::DigitCount),
+            "punctuation_count" => Some(Field::PunctuationCount),
+            "first_character" => Some(Field::FirstCharacter),
+            "last_character" => Some(Field::LastCharacter),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Length => "length",
+            Field::WordCount => "word_count",
+            Field::UniqueCharacters => "unique_characters",
+            Field::IsPalindrome => "is_palindrome",
+            Field::Value => "value",
+            Field::VowelCount => "vowel_count",
+            Field::ConsonantCount => "consonant_count",
+            Field::DigitCount => "digit_count",
+            Field::PunctuationCount => "punctuation_count",
+            Field::FirstCharacter => "first_character",
+            Field::LastCharacter => "last_character",
+        }
+    }
+
+    /// The count field for a character class.
+    pub fn count_of(class: CharClass) -> Self {
+        match class {
+            CharClass::Vowel => Field::VowelCount,
+            CharClass::Consonant => Field::ConsonantCount,
+            CharClass::Digit => Field::DigitCount,
+            CharClass::Punctuation => Field::PunctuationCount,
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        self.counted_class().is_some() || matches!(self, Field::Length | Field::WordCount | Field::UniqueCharacters)
+    }
+
+    fn is_character(self) -> bool {
+        matches!(self, Field::FirstCharacter | Field::LastCharacter)
+    }
+
+    fn counted_class(self) -> Option<CharClass> {
+        CharClass::ALL.into_iter().find(|&class| Field::count_of(class) == self)
+    }
+
+    fn numeric(self, stored: &StoredString) -> i64 {
+        let props = &stored.properties;
+        match self {
+            Field::Length => props.length as i64,
+            Field::WordCount => props.word_count as i64,
+            Field::UniqueCharacters => props.unique_characters as i64,
+            Field::VowelCount => props.vowel_count as i64,
+            Field::ConsonantCount => props.consonant_count as i64,
+            Field::DigitCount => props.digit_count as i64,
+            Field::PunctuationCount => props.punctuation_count as i64,
+            _ => 0,
+        }
+    }
+
+    fn character(self, stored: &StoredString) -> Option<&str> {
+        let mut graphemes = stored.value.graphemes(true);
+        match self {
+            Field::FirstCharacter => graphemes.next(),
+            Field::LastCharacter => graphemes.next_back(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        }
+    }
+
+    fn apply<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            CmpOp::Eq => left == right,
+            CmpOp::Ne => left != right,
+            CmpOp::Lt => left < right,
+            CmpOp::Le => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Ge => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    Int(i64),
+    Bool(bool),
+    Str(String),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Int(n) => write!(f, "{}", n),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Str(s) => write!(f, "'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+/// Parsed `filter=` expression. Serializes back to its canonical text form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare { field: Field, op: CmpOp, value: Literal },
+    In { field: Field, values: Vec<Literal> },
+    Contains { field: Field, value: String },
+    Is { field: Field, class: CharClass },
+}
+
+impl FilterExpr {
+    pub fn parse(input: &str) -> Result<Self, FilterSyntaxError> {
+        if input.chars().count() > MAX_EXPRESSION_LENGTH {
+            return Err(FilterSyntaxError::new(
+                format!("Expression longer than {} characters", MAX_EXPRESSION_LENGTH),
+                MAX_EXPRESSION_LENGTH + 1,
+            ));
+        }
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            end_column: input.chars().count() + 1,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(FilterSyntaxError::new(
+                format!("Unexpected {}", token.kind.describe()),
+                token.column,
+            ));
+        }
+        Ok(expr)
+    }
+
+    pub fn matches(&self, stored: &StoredString) -> bool {
+        match self {
+            FilterExpr::And(a, b) => a.matches(stored) && b.matches(stored),
+            FilterExpr::Or(a, b) => a.matches(stored) || b.matches(stored),
+            FilterExpr::Not(inner) => !inner.matches(stored),
+            FilterExpr::Compare { field, op, value } => compare(stored, *field, *op, value),
+            FilterExpr::In { field, values } => values
+                .iter()
+                .any(|value| compare(stored, *field, CmpOp::Eq, value)),
+            FilterExpr::Contains { value, .. } => stored.value.contains(value.as_str()),
+            FilterExpr::Is { field, class } => field.character(stored).and_then(char_class) == Some(*class),
+        }
+    }
+}
+
+fn compare(stored: &StoredString, field: Field, op: CmpOp, value: &Literal) -> bool {
+    match (field, value) {
+        (Field::IsPalindrome, Literal::Bool(b)) => op.apply(stored.properties.is_palindrome, *b),
+        (Field::Value, Literal::Str(s)) => op.apply(stored.value.as_str(), s.as_str()),
+        (field, Literal::Int(n)) if field.is_numeric() => op.apply(field.numeric(stored), *n),
+        _ => false,
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterExpr::And(a, b) => write!(f, "({} AND {})", a, b),
+            FilterExpr::Or(a, b) => write!(f, "({} OR {})", a, b),
+            FilterExpr::Not(inner) => write!(f, "NOT {}", inner),
+            FilterExpr::Compare { field, op, value } => {
+                write!(f, "{} {} {}", field.name(), op.symbol(), value)
+            }
+            FilterExpr::In { field, values } => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "{} IN ({})", field.name(), values.join(", "))
+            }
+            FilterExpr::Contains { field, value } => {
+                write!(f, "{} CONTAINS {}", field.name(), Literal::Str(value.clone()))
+            }
+            FilterExpr::Is { field, class } => write!(f, "{} IS {}", field.name(), class.name()),
+        }
+    }
+}
+
+impl TryFrom<String> for FilterExpr {
+    type Error = FilterSyntaxError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        FilterExpr::parse(&s)
+    }
+}
+
+impl From<FilterExpr> for String {
+    fn from(expr: FilterExpr) -> Self {
+        expr.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Ident(s) => format!("'{}'", s),
+            TokenKind::Int(n) => format!("number {}", n),
+            TokenKind::Str(s) => format!("string '{}'", s),
+            TokenKind::Op(op) => format!("operator '{}'", op.symbol()),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::Comma => "','".to_string(),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, TokenKind::Ident(s) if s.eq_ignore_ascii_case(keyword))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterSyntaxError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = match c {
+            '(' => {
+                i += 1;
+                TokenKind::LParen
+            }
+            ')' => {
+                i += 1;
+                TokenKind::RParen
+            }
+            ',' => {
+                i += 1;
+                TokenKind::Comma
+            }
+            '=' | '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let (op, width) = match (c, next) {
+                    ('=', Some('=')) => (CmpOp::Eq, 2),
+                    ('=', _) => (CmpOp::Eq, 1),
+                    ('!', Some('=')) => (CmpOp::Ne, 2),
+                    ('<', Some('>')) => (CmpOp::Ne, 2),
+                    ('<', Some('=')) => (CmpOp::Le, 2),
+                    ('<', _) => (CmpOp::Lt, 1),
+                    ('>', Some('=')) => (CmpOp::Ge, 2),
+                    ('>', _) => (CmpOp::Gt, 1),
+                    _ => return Err(FilterSyntaxError::new("Expected '=' after '!'", column)),
+                };
+                i += width;
+                TokenKind::Op(op)
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(FilterSyntaxError::new("Unterminated string literal", column)),
+                        Some(&ch) if ch == quote => {
+                            if chars.get(i + 1) == Some(&quote) {
+                                value.push(quote);
+                                i += 2;
+                            } else {
+                                i += 1;
+                                break;
+                            }
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                TokenKind::Str(value)
+            }
+            c if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text
+                    .parse::<i64>()
+                    .map_err(|_| FilterSyntaxError::new(format!("Invalid number {}", text), column))?;
+                TokenKind::Int(n)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                TokenKind::Ident(chars[start..i].iter().collect())
+            }
+            other => {
+                return Err(FilterSyntaxError::new(format!("Unexpected character '{}'", other), column));
+            }
+        };
+
+        tokens.push(Token { kind, column });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, FilterSyntaxError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| FilterSyntaxError::new("Unexpected end of expression", self.end_column))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.kind.is_keyword(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), FilterSyntaxError> {
+        let token = self.next()?;
+        if token.kind == kind {
+            Ok(())
+        } else {
+            Err(FilterSyntaxError::new(
+                format!("Expected {}, found {}", kind.describe(), token.kind.describe()),
+                token.column,
+            ))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, FilterSyntaxError> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = FilterExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, FilterSyntaxError> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_not()?;
+            left = FilterExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<FilterExpr, FilterSyntaxError> {
+        if self.eat_keyword("not") {
+            let inner = self.nested(|p| p.parse_not())?;
+            return Ok(FilterExpr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn nested<F>(&mut self, f: F) -> Result<FilterExpr, FilterSyntaxError>
+    where
+        F: FnOnce(&mut Self) -> Result<FilterExpr, FilterSyntaxError>,
+    {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let column = self.peek().map(|t| t.column).unwrap_or(self.end_column);
+            return Err(FilterSyntaxError::new("Expression nested too deeply", column));
+        }
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterExpr, FilterSyntaxError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::LParen => {
+                let expr = self.nested(|p| p.parse_or())?;
+                self.expect(TokenKind::RParen)?;
+                Ok(expr)
+            }
+            TokenKind::Ident(ref name) => {
+                let field = Field::parse(name).ok_or_else(|| {
+                    FilterSyntaxError::new(format!("Unknown field '{}'", name), token.column)
+                })?;
+                self.parse_condition(field)
+            }
+            other => Err(FilterSyntaxError::new(
+                format!("Expected a field name, found {}", other.describe()),
+                token.column,
+            )),
+        }
+    }
+
+    fn parse_condition(&mut self, field: Field) -> Result<FilterExpr, FilterSyntaxError> {
+        if field.is_character() {
+            return self.parse_class(field);
+        }
+
+        if self.eat_keyword("in") {
+            self.expect(TokenKind::LParen)?;
+            let mut values = vec![self.parse_literal(field)?];
+            while self.peek().is_some_and(|t| t.kind == TokenKind::Comma) {
+                self.pos += 1;
+                values.push(self.parse_literal(field)?);
+            }
+            self.expect(TokenKind::RParen)?;
+            return Ok(FilterExpr::In { field, values });
+        }
+
+        if self.peek().is_some_and(|t| t.kind.is_keyword("contains")) {
+            let column = self.next()?.column;
+            if field != Field::Value {
+                return Err(FilterSyntaxError::new(
+                    format!("CONTAINS is only supported on value, not {}", field.name()),
+                    column,
+                ));
+            }
+            return match self.parse_literal(field)? {
+                Literal::Str(value) => Ok(FilterExpr::Contains { field, value }),
+                _ => unreachable!("value literals are always strings"),
+            };
+        }
+
+        let op = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Op(op)) => *op,
+            // A bare boolean field reads as `field = true`.
+            _ if field == Field::IsPalindrome => {
+                return Ok(FilterExpr::Compare {
+                    field,
+                    op: CmpOp::Eq,
+                    value: Literal::Bool(true),
+                });
+            }
+            _ => {
+                let column = self.peek().map(|t| t.column).unwrap_or(self.end_column);
+                return Err(FilterSyntaxError::new(
+                    format!("Expected a comparison operator after {}", field.name()),
+                    column,
+                ));
+            }
+        };
+        let op_column = self.next()?.column;
+
+        if !field.is_numeric() && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+            return Err(FilterSyntaxError::new(
+                format!("Operator '{}' is not supported on {}", op.symbol(), field.name()),
+                op_column,
+            ));
+        }
+
+        let value = self.parse_literal(field)?;
+        Ok(FilterExpr::Compare { field, op, value })
+    }
+
+    /// `IS vowel`, `IS digit`, ... after `first_character` or `last_character`.
+    fn parse_class(&mut self, field: Field) -> Result<FilterExpr, FilterSyntaxError> {
+        let token = self.next()?;
+        if !token.kind.is_keyword("is") {
+            return Err(FilterSyntaxError::new(
+                format!("Expected IS after {}, found {}", field.name(), token.kind.describe()),
+                token.column,
+            ));
+        }
+        let token = self.next()?;
+        let class = match &token.kind {
+            TokenKind::Ident(name) => CharClass::parse(name),
+            _ => None,
+        };
+        class.map(|class| FilterExpr::Is { field, class }).ok_or_else(|| {
+            FilterSyntaxError::new(
+                format!("Expected vowel, consonant, digit or punctuation, found {}", token.kind.describe()),
+                token.column,
+            )
+        })
+    }
+
+    fn parse_literal(&mut self, field: Field) -> Result<Literal, FilterSyntaxError> {
+        let token = self.next()?;
+        let literal = match (&token.kind, field) {
+            (TokenKind::Int(n), f) if f.is_numeric() => Some(Literal::Int(*n)),
+            (TokenKind::Ident(s), Field::IsPalindrome) if s.eq_ignore_ascii_case("true") => Some(Literal::Bool(true)),
+            (TokenKind::Ident(s), Field::IsPalindrome) if s.eq_ignore_ascii_case("false") => Some(Literal::Bool(false)),
+            (TokenKind::Str(s), Field::Value) => Some(Literal::Str(s.clone())),
+            _ => None,
+        };
+        literal.ok_or_else(|| {
+            let expected = match field {
+                Field::IsPalindrome => "true or false",
+                Field::Value => "a quoted string",
+                _ => "an integer",
+            };
+            FilterSyntaxError::new(
+                format!("Expected {} for {}, found {}", expected, field.name(), token.kind.describe()),
+                token.column,
+            )
+        })
+    }
+}
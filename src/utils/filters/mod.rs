@@ -0,0 +1,234 @@
+pub mod expr;
+
+use crate::error::{AppError, ParamErrors};
+use crate::models::string::{ContainsMode, Filters};
+use actix_web::web::Query;
+use chrono::{DateTime, Utc};
+use expr::FilterExpr;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const MAX_SUBSTRING_LENGTH: usize = 256;
+pub const MAX_PATTERN_LENGTH: usize = 256;
+const PATTERN_SIZE_LIMIT: usize = 1 << 16;
+
+/// Compiled `matches=` regex. Pattern length and compiled size are capped so a
+/// single query cannot blow up memory or matching time.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        Self::build(pattern, false)
+    }
+
+    /// A pattern that ignores case, as if it started with `(?i)`.
+    pub fn case_insensitive(pattern: &str) -> Result<Self, String> {
+        Self::build(pattern, true)
+    }
+
+    fn build(pattern: &str, case_insensitive: bool) -> Result<Self, String> {
+        if pattern.chars().count() > MAX_PATTERN_LENGTH {
+            return Err(format!("matches must be at most {} characters", MAX_PATTERN_LENGTH));
+        }
+        RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .size_limit(PATTERN_SIZE_LIMIT)
+            .dfa_size_limit(PATTERN_SIZE_LIMIT)
+            .nest_limit(32)
+            .build()
+            .map(Pattern)
+            .map_err(|e| format!("Invalid matches pattern: {}", e))
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Pattern::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryParams {
+    pub is_palindrome: Option<String>,
+    pub min_length: Option<String>,
+    pub max_length: Option<String>,
+    pub word_count: Option<String>,
+    pub contains_character: Option<String>,
+    pub contains_characters: Option<String>,
+    pub contains_mode: Option<String>,
+    pub case_insensitive: Option<String>,
+    pub contains: Option<String>,
+    pub starts_with: Option<String>,
+    pub ends_with: Option<String>,
+    pub matches: Option<String>,
+    pub min_word_count: Option<String>,
+    pub max_word_count: Option<String>,
+    pub min_unique_characters: Option<String>,
+    pub max_unique_characters: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub filter: Option<String>,
+    pub limit: Option<String>,
+    pub cursor: Option<String>,
+    pub sort_by: Option<String>,
+    pub order: Option<String>,
+}
+
+pub fn parse_filters(query: Query<QueryParams>) -> Result<Filters, AppError> {
+    let mut errors = ParamErrors::default();
+    let is_palindrome = errors.parse("is_palindrome", query.is_palindrome.as_deref(), parse_bool);
+    let min_length = errors.parse("min_length", query.min_length.as_deref(), parse_usize);
+    let max_length = errors.parse("max_length", query.max_length.as_deref(), parse_usize);
+    let word_count = errors.parse("word_count", query.word_count.as_deref(), parse_usize);
+    let min_word_count = errors.parse("min_word_count", query.min_word_count.as_deref(), parse_usize);
+    let max_word_count = errors.parse("max_word_count", query.max_word_count.as_deref(), parse_usize);
+    let min_unique_characters = errors.parse("min_unique_characters", query.min_unique_characters.as_deref(), parse_usize);
+    let max_unique_characters = errors.parse("max_unique_characters", query.max_unique_characters.as_deref(), parse_usize);
+    let contains_character = errors.parse("contains_character", query.contains_character.as_deref(), parse_grapheme);
+    let contains_characters = errors.parse("contains_characters", query.contains_characters.as_deref(), parse_grapheme_list);
+    let contains_mode = errors.parse("contains_mode", query.contains_mode.as_deref(), parse_contains_mode);
+    let case_insensitive = errors.parse("case_insensitive", query.case_insensitive.as_deref(), parse_bool);
+    let contains = errors.parse("contains", query.contains.as_deref(), |s| parse_substring("contains", s));
+    let starts_with = errors.parse("starts_with", query.starts_with.as_deref(), |s| parse_substring("starts_with", s));
+    let ends_with = errors.parse("ends_with", query.ends_with.as_deref(), |s| parse_substring("ends_with", s));
+    // case_insensitive applies to every text filter, the regex included.
+    let compile = if case_insensitive == Some(true) { Pattern::case_insensitive } else { Pattern::new };
+    let matches = errors.parse("matches", query.matches.as_deref(), compile);
+    let created_after = errors.parse("created_after", query.created_after.as_deref(), parse_datetime);
+    let created_before = errors.parse("created_before", query.created_before.as_deref(), parse_datetime);
+
+    if query.contains_mode.is_some() && query.contains_characters.is_none() {
+        errors.push("contains_mode", "contains_mode requires contains_characters");
+    }
+    let contains_mode = contains_characters.as_ref().map(|_| contains_mode.unwrap_or_default());
+
+    check_range(&mut errors, "min_length", min_length, "max_length", max_length);
+    check_range(&mut errors, "min_word_count", min_word_count, "max_word_count", max_word_count);
+    check_range(&mut errors, "min_unique_characters", min_unique_characters, "max_unique_characters", max_unique_characters);
+    check_range(&mut errors, "created_after", created_after, "created_before", created_before);
+    errors.finish()?;
+
+    Ok(Filters {
+        is_palindrome,
+        min_length,
+        max_length,
+        word_count,
+        contains_character,
+        contains_characters,
+        contains_mode,
+        case_insensitive,
+        contains,
+        starts_with,
+        ends_with,
+        matches,
+        min_word_count,
+        max_word_count,
+        min_unique_characters,
+        max_unique_characters,
+        created_after,
+        created_before,
+        filter: None,
+    })
+}
+
+pub fn parse_filter_expression(query: &QueryParams) -> Result<Option<FilterExpr>, AppError> {
+    let Some(filter) = query.filter.as_deref() else {
+        return Ok(None);
+    };
+    FilterExpr::parse(filter).map(Some).map_err(|e| AppError::FilterSyntax {
+        filter: filter.to_string(),
+        message: e.message,
+        column: e.column,
+    })
+}
+
+pub(crate) fn parse_bool(s: &str) -> Result<bool, String> {
+    match s.to_lowercase().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("Invalid boolean value: {}", s)),
+    }
+}
+
+fn parse_usize(s: &str) -> Result<usize, String> {
+    s.parse::<usize>()
+        .map_err(|_| format!("Invalid integer value: {}", s))
+}
+
+/// A single user-perceived character, such as "é" written with a combining
+/// accent or a ZWJ emoji sequence.
+fn parse_grapheme(s: &str) -> Result<String, String> {
+    if s.graphemes(true).count() == 1 {
+        Ok(s.to_string())
+    } else {
+        Err("contains_character must be a single character".to_string())
+    }
+}
+
+fn parse_grapheme_list(s: &str) -> Result<Vec<String>, String> {
+    let mut result = Vec::new();
+    for item in s.split(',') {
+        let grapheme = parse_grapheme(item)
+            .map_err(|_| format!("contains_characters entries must be single characters: {:?}", item))?;
+        if !result.contains(&grapheme) {
+            result.push(grapheme);
+        }
+    }
+    Ok(result)
+}
+
+fn parse_contains_mode(s: &str) -> Result<ContainsMode, String> {
+    match s.to_lowercase().as_str() {
+        "all" => Ok(ContainsMode::All),
+        "any" => Ok(ContainsMode::Any),
+        _ => Err(format!("Invalid contains_mode value: {}", s)),
+    }
+}
+
+fn parse_substring(name: &str, s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        return Err(format!("{} cannot be empty", name));
+    }
+    if s.chars().count() > MAX_SUBSTRING_LENGTH {
+        return Err(format!("{} must be at most {} characters", name, MAX_SUBSTRING_LENGTH));
+    }
+    Ok(s.to_string())
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid RFC 3339 timestamp: {}", s))
+}
+
+fn check_range<T: PartialOrd + Display>(
+    errors: &mut ParamErrors,
+    min_name: &str,
+    min: Option<T>,
+    max_name: &str,
+    max: Option<T>,
+) {
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            errors.push(min_name, format!("{} cannot be greater than {}", min_name, max_name));
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use crate::error::AppError;
+use actix_web::{web, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Largest body `Body<T>` will buffer; the same as actix's JSON extractor default.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Wire formats negotiated through `Accept` and `Content-Type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    MessagePack,
+    Cbor,
+    Yaml,
+    /// One row per item; nested objects become dotted columns.
+    Csv,
+}
+
+impl Format {
+    pub fn from_media_type(media: &str) -> Option<Self> {
+        match media.trim().to_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(Format::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Some(Format::MessagePack),
+            "application/cbor" => Some(Format::Cbor),
+            "application/yaml" | "application/x-yaml" | "text/yaml" => Some(Format::Yaml),
+            "text/csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::MessagePack => "application/msgpack",
+            Format::Cbor => "application/cbor",
+            Format::Yaml => "application/yaml",
+            Format::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    /// Picks the supported format with the highest `q` in `Accept`, falling
+    /// back to JSON when the header is missing or names nothing we produce.
+    pub fn from_accept(req: &HttpRequest) -> Self {
+        let accept = req.headers().get_all(header::ACCEPT).filter_map(|v| v.to_str().ok());
+        preferred(accept, Format::from_media_type).unwrap_or_default()
+    }
+
+    /// Format of the request body. A missing `Content-Type` is read as JSON.
+    pub fn from_content_type(req: &HttpRequest) -> Result<Self, String> {
+        let Some(content_type) = req.headers().get(header::CONTENT_TYPE) else {
+            return Ok(Format::Json);
+        };
+        let content_type = content_type.to_str().map_err(|_| "Invalid Content-Type header".to_string())?;
+        let media = content_type.split(';').next().unwrap_or_default();
+        Format::from_media_type(media)
+            .filter(|_| !media.contains('*'))
+            .ok_or_else(|| format!("Unsupported Content-Type: {}", content_type))
+    }
+
+    pub fn encode<T: Serialize>(self, data: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Format::Json => serde_json::to_vec(data).map_err(|e| e.to_string()),
+            Format::MessagePack => rmp_serde::to_vec_named(data).map_err(|e| e.to_string()),
+            Format::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(data, &mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            }
+            Format::Yaml => serde_yaml::to_string(data).map(String::into_bytes).map_err(|e| e.to_string()),
+            Format::Csv => encode_csv(&serde_json::to_value(data).map_err(|e| e.to_string())?),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            Format::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+            Format::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+            Format::Yaml => serde_yaml::from_slice(bytes).map_err(|e| e.to_string()),
+            Format::Csv => serde_json::from_value(decode_csv(bytes)?).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Reads the comma-separated entries of a weighted header such as `Accept` or
+/// `Accept-Language`, and returns what `pick` makes of the entry with the
+/// highest `q`. Entries `pick` rejects and entries with `q=0` are skipped;
+/// equal weights keep the client's order.
+pub fn preferred<'a, T>(
+    values: impl IntoIterator<Item = &'a str>,
+    mut pick: impl FnMut(&str) -> Option<T>,
+) -> Option<T> {
+    let mut candidates: Vec<(f32, T)> = values
+        .into_iter()
+        .flat_map(|v| v.split(','))
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let item = pick(parts.next()?)?;
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (q > 0.0).then_some((q, item))
+        })
+        .collect();
+    // Stable, so equal weights keep the client's order.
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    candidates.into_iter().next().map(|(_, item)| item)
+}
+
+/// Request body decoded according to its `Content-Type`, in any `Format`.
+pub struct Body<T>(pub T);
+
+impl<T> Body<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Body<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for Body<T> {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let format = Format::from_content_type(req);
+        let mut payload = payload.take();
+        Box::pin(async move {
+            let format = format.map_err(AppError::UnsupportedMediaType)?;
+            let mut body = web::BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(|e| AppError::InvalidBody(e.to_string()))?;
+                if body.len() + chunk.len() > MAX_BODY_BYTES {
+                    return Err(AppError::PayloadTooLarge("Request body is too large".to_string()));
+                }
+                body.extend_from_slice(&chunk);
+            }
+            format.decode(&body).map(Body).map_err(AppError::InvalidBody)
+        })
+    }
+}
+
+/// Rows of a CSV response: the `data` array of a listing, a bare array, or
+/// the value itself as a single row.
+fn csv_rows(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => match map.get("data") {
+            Some(Value::Array(items)) => items.iter().collect(),
+            _ => vec![value],
+        },
+        other => vec![other],
+    }
+}
+
+fn flatten_into(prefix: &str, value: &Value, row: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, nested) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_into(&key, nested, row);
+            }
+        }
+        other => {
+            row.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+/// Flattens every row into dotted columns, so `properties.character_frequency_map.a`
+/// becomes a column of its own. The header is the union of all columns.
+fn encode_csv(value: &Value) -> Result<Vec<u8>, String> {
+    let rows: Vec<Map<String, Value>> = csv_rows(value)
+        .into_iter()
+        .map(|item| {
+            let mut row = Map::new();
+            flatten_into("", item, &mut row);
+            row
+        })
+        .collect();
+    let columns: BTreeSet<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns).map_err(|e| e.to_string())?;
+    for row in &rows {
+        let cells = columns.iter().map(|column| match row.get(*column) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        });
+        writer.write_record(cells).map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+/// Reads a CSV body as one object per row, nesting dotted column names. All
+/// cells are strings and empty cells are left out. A single row decodes to an
+/// object, several to an array.
+fn decode_csv(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = csv::Reader::from_reader(bytes);
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let mut row = Map::new();
+        for (column, cell) in headers.iter().zip(record.iter()).filter(|(_, cell)| !cell.is_empty()) {
+            let mut target = &mut row;
+            let mut path = column.split('.').peekable();
+            while let Some(key) = path.next() {
+                if path.peek().is_none() {
+                    target.insert(key.to_string(), Value::String(cell.to_string()));
+                } else {
+                    target = target
+                        .entry(key.to_string())
+                        .or_insert_with(|| Value::Object(Map::new()))
+                        .as_object_mut()
+                        .ok_or_else(|| format!("Column {} conflicts with another column", column))?;
+                }
+            }
+        }
+        rows.push(Value::Object(row));
+    }
+    match rows.len() {
+        1 => Ok(rows.remove(0)),
+        _ => Ok(Value::Array(rows)),
+    }
+}
@@ -1,2 +1,5 @@
 pub mod filters;
+pub mod format;
+pub mod pagination;
+pub mod projection;
 pub mod response;
\ No newline at end of file
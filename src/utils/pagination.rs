@@ -0,0 +1,146 @@
+use crate::error::{AppError, ParamErrors};
+use crate::models::string::{SortKey, SortOrder, StoredString};
+use crate::utils::filters::QueryParams;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+pub const MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Default)]
+pub struct Pagination {
+    pub sort_by: SortKey,
+    pub order: SortOrder,
+    pub limit: Option<usize>,
+    pub cursor: Option<Cursor>,
+}
+
+/// Position after which the next page starts. Pages are keyed on the sort
+/// value plus the id, so inserts elsewhere in the store never shift a page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cursor {
+    sort_by: SortKey,
+    order: SortOrder,
+    key: i64,
+    id: String,
+}
+
+impl Cursor {
+    fn after(stored: &StoredString, pagination: &Pagination) -> Self {
+        Cursor {
+            sort_by: pagination.sort_by,
+            order: pagination.order,
+            key: sort_value(stored, pagination.sort_by),
+            id: stored.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(s: &str) -> Result<Self, String> {
+        URL_SAFE_NO_PAD
+            .decode(s)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| "Invalid cursor".to_string())
+    }
+}
+
+pub fn parse_pagination(query: &QueryParams) -> Result<Pagination, AppError> {
+    let mut errors = ParamErrors::default();
+    let sort_by = errors.parse("sort_by", query.sort_by.as_deref(), parse_sort_key).unwrap_or_default();
+    let order = errors.parse("order", query.order.as_deref(), parse_sort_order).unwrap_or_default();
+    let limit = errors.parse("limit", query.limit.as_deref(), parse_limit);
+    let cursor = errors.parse("cursor", query.cursor.as_deref(), Cursor::decode);
+
+    if let Some(cursor) = &cursor {
+        if cursor.sort_by != sort_by || cursor.order != order {
+            errors.push("cursor", "cursor does not match sort_by and order");
+        }
+    }
+    errors.finish()?;
+
+    Ok(Pagination {
+        sort_by,
+        order,
+        limit,
+        cursor,
+    })
+}
+
+impl Pagination {
+    /// Rows a store fetches for one page: one past `limit` shows whether
+    /// another page follows.
+    pub fn fetch_limit(&self) -> Option<usize> {
+        self.limit.map(|limit| limit + 1)
+    }
+
+    /// The sort value and id of the last row of the previous page.
+    pub fn after(&self) -> Option<(i64, &str)> {
+        self.cursor.as_ref().map(|cursor| (cursor.key, cursor.id.as_str()))
+    }
+
+    /// Whether `stored` comes after the cursor in page order.
+    pub fn is_after_cursor(&self, stored: &StoredString) -> bool {
+        let Some(after) = self.after() else {
+            return true;
+        };
+        let ord = (sort_value(stored, self.sort_by), stored.id.as_str()).cmp(&after);
+        match self.order {
+            SortOrder::Asc => ord == Ordering::Greater,
+            SortOrder::Desc => ord == Ordering::Less,
+        }
+    }
+}
+
+/// Cuts rows a store fetched in page order, starting after the cursor, at
+/// `limit`. Returns the page and the cursor for the following one, if any.
+pub fn finish_page(mut rows: Vec<StoredString>, pagination: &Pagination) -> (Vec<StoredString>, Option<Cursor>) {
+    let mut next_cursor = None;
+    if let Some(limit) = pagination.limit {
+        if rows.len() > limit {
+            rows.truncate(limit);
+            next_cursor = rows.last().map(|last| Cursor::after(last, pagination));
+        }
+    }
+    (rows, next_cursor)
+}
+
+/// The value a string is ordered by under `key`; ties are broken by id.
+pub fn sort_value(stored: &StoredString, key: SortKey) -> i64 {
+    match key {
+        SortKey::CreatedAt => stored.created_at.timestamp_micros(),
+        SortKey::Length => stored.properties.length as i64,
+        SortKey::WordCount => stored.properties.word_count as i64,
+        SortKey::UniqueCharacters => stored.properties.unique_characters as i64,
+    }
+}
+
+fn parse_limit(s: &str) -> Result<usize, String> {
+    let limit = s.parse::<usize>().map_err(|_| format!("Invalid integer value: {}", s))?;
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(format!("limit must be between 1 and {}", MAX_LIMIT));
+    }
+    Ok(limit)
+}
+
+fn parse_sort_key(s: &str) -> Result<SortKey, String> {
+    match s.to_lowercase().as_str() {
+        "created_at" => Ok(SortKey::CreatedAt),
+        "length" => Ok(SortKey::Length),
+        "word_count" => Ok(SortKey::WordCount),
+        "unique_characters" => Ok(SortKey::UniqueCharacters),
+        _ => Err(format!("Invalid sort_by value: {}", s)),
+    }
+}
+
+fn parse_sort_order(s: &str) -> Result<SortOrder, String> {
+    match s.to_lowercase().as_str() {
+        "asc" => Ok(SortOrder::Asc),
+        "desc" => Ok(SortOrder::Desc),
+        _ => Err(format!("Invalid order value: {}", s)),
+    }
+}
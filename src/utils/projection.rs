@@ -0,0 +1,131 @@
+use crate::error::{AppError, ParamErrors};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Top-level fields a projection may name. `properties.*` may go to any depth,
+/// since custom analyzers add their own properties.
+const ROOT_FIELDS: &[&str] = &["id", "value", "properties", "created_at"];
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ProjectionQuery {
+    /// Comma-separated dotted paths to keep, e.g. `id,value,properties.length`.
+    pub fields: Option<String>,
+    /// Comma-separated dotted paths to drop.
+    pub exclude: Option<String>,
+}
+
+/// Field selection for `StoredString`-shaped responses. Listing responses
+/// are projected entry by entry in their `data` array.
+#[derive(Debug, Clone, Default)]
+pub struct Projection {
+    include: Option<Vec<Vec<String>>>,
+    exclude: Vec<Vec<String>>,
+}
+
+impl Projection {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_empty()
+    }
+
+    /// Whether the field at `path` survives the projection, either itself or
+    /// through a requested descendant.
+    pub fn wants(&self, path: &[&str]) -> bool {
+        let overlaps = |p: &Vec<String>| p.iter().zip(path).all(|(a, b)| a == b);
+        let included = self.include.as_ref().is_none_or(|paths| paths.iter().any(overlaps));
+        let excluded = self.exclude.iter().any(|p| p.len() <= path.len() && overlaps(p));
+        included && !excluded
+    }
+
+    /// Whether the property `name` has to be computed.
+    pub fn wants_property(&self, name: &str) -> bool {
+        self.wants(&["properties", name])
+    }
+
+    pub fn apply(&self, mut value: Value) -> Value {
+        if self.is_empty() {
+            return value;
+        }
+        match value.get_mut("data") {
+            Some(Value::Array(items)) => {
+                for item in items.iter_mut() {
+                    self.apply_one(item);
+                }
+            }
+            _ => self.apply_one(&mut value),
+        }
+        value
+    }
+
+    fn apply_one(&self, value: &mut Value) {
+        if let Some(paths) = &self.include {
+            let paths: Vec<&[String]> = paths.iter().map(Vec::as_slice).collect();
+            retain_paths(value, &paths);
+        }
+        for path in &self.exclude {
+            remove_path(value, path);
+        }
+    }
+}
+
+pub fn parse_projection(query: &ProjectionQuery) -> Result<Projection, AppError> {
+    let mut errors = ParamErrors::default();
+    let include = errors.parse("fields", query.fields.as_deref(), parse_paths);
+    let exclude = errors.parse("exclude", query.exclude.as_deref(), parse_paths);
+    errors.finish()?;
+    Ok(Projection {
+        include,
+        exclude: exclude.unwrap_or_default(),
+    })
+}
+
+fn parse_paths(list: &str) -> Result<Vec<Vec<String>>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            let path: Vec<String> = field.split('.').map(str::to_string).collect();
+            if !ROOT_FIELDS.contains(&path[0].as_str()) || path.iter().any(String::is_empty) {
+                return Err(format!("Unknown field: {}", field));
+            }
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Keeps only the parts of `value` on or under one of `paths`.
+fn retain_paths(value: &mut Value, paths: &[&[String]]) {
+    if paths.iter().any(|p| p.is_empty()) {
+        return;
+    }
+    let Value::Object(map) = value else {
+        return;
+    };
+    map.retain(|key, nested| {
+        let rest: Vec<&[String]> = paths
+            .iter()
+            .filter(|p| p[0] == *key)
+            .map(|p| &p[1..])
+            .collect();
+        if rest.is_empty() {
+            return false;
+        }
+        retain_paths(nested, &rest);
+        true
+    });
+}
+
+fn remove_path(value: &mut Value, path: &[String]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut target = value;
+    for key in parents {
+        match target.get_mut(key) {
+            Some(nested) => target = nested,
+            None => return,
+        }
+    }
+    if let Value::Object(map) = target {
+        map.remove(last);
+    }
+}
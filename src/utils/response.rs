@@ -1,17 +1,89 @@
-use actix_web::{HttpResponse, http::StatusCode};
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError, http::{header, StatusCode}};
+use crate::error::AppError;
+use crate::utils::format::Format;
+use crate::utils::projection::Projection;
+use futures_util::stream;
 use serde::Serialize;
+use serde_json::json;
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
+pub const NDJSON: &str = "application/x-ndjson";
+
+/// Serializes `data` in the format negotiated from the request's `Accept` header.
+pub fn success_response<T: Serialize>(req: &HttpRequest, data: T, status: StatusCode) -> HttpResponse {
+    let format = Format::from_accept(req);
+    match format.encode(&data) {
+        Ok(body) => HttpResponse::build(status).content_type(format.content_type()).body(body),
+        Err(e) => AppError::Internal(format!("Failed to encode response: {}", e)).error_response(),
+    }
+}
+
+/// `success_response` with `projection` applied to `data` first.
+pub fn projected_response<T: Serialize>(
+    req: &HttpRequest,
+    data: T,
+    projection: &Projection,
+    status: StatusCode,
+) -> HttpResponse {
+    if projection.is_empty() {
+        return success_response(req, data, status);
+    }
+    match serde_json::to_value(data) {
+        Ok(value) => success_response(req, projection.apply(value), status),
+        Err(e) => AppError::Internal(format!("Failed to encode response: {}", e)).error_response(),
+    }
 }
 
-pub fn success_response<T: Serialize>(data: T, status: StatusCode) -> HttpResponse {
-    HttpResponse::build(status).json(data)
+/// Whether the client asked for newline-delimited JSON.
+pub fn accepts_ndjson(req: &HttpRequest) -> bool {
+    req.headers()
+        .get_all(header::ACCEPT)
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|media| media.split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case(NDJSON))
 }
 
-pub fn error_response(message: &str, status: StatusCode) -> HttpResponse {
-    HttpResponse::build(status).json(ErrorResponse {
-        error: message.to_string(),
-    })
-}
\ No newline at end of file
+/// Streams `items` one JSON object per line, followed by `{"summary": ...}`.
+/// Items are pulled, serialized and projected only when the body is polled,
+/// so the response never holds the whole listing. `summary` is built once the
+/// items run out, from the exhausted iterator and the number of items. An
+/// item that fails ends the body early, without a summary.
+pub fn ndjson_response<I, T, S, F>(items: I, projection: &Projection, summary: F) -> HttpResponse
+where
+    I: Iterator<Item = Result<T, AppError>> + 'static,
+    T: Serialize,
+    S: Serialize,
+    F: FnOnce(&I, usize) -> S + 'static,
+{
+    let projection = projection.clone();
+    let mut items = items;
+    let mut summary = Some(summary);
+    let mut count = 0;
+    let lines = std::iter::from_fn(move || {
+        let line = match items.next() {
+            Some(Ok(item)) => {
+                count += 1;
+                if projection.is_empty() {
+                    serde_json::to_vec(&item)
+                } else {
+                    serde_json::to_value(&item).and_then(|value| serde_json::to_vec(&projection.apply(value)))
+                }
+            }
+            Some(Err(e)) => {
+                summary = None;
+                return Some(Err(actix_web::Error::from(e)));
+            }
+            None => {
+                let summary = summary.take()?;
+                serde_json::to_vec(&json!({ "summary": summary(&items, count) }))
+            }
+        };
+        Some(
+            line.map(|mut bytes| {
+                bytes.push(b'\n');
+                web::Bytes::from(bytes)
+            })
+            .map_err(actix_web::error::ErrorInternalServerError),
+        )
+    });
+    HttpResponse::Ok().content_type(NDJSON).streaming(stream::iter(lines))
+}